@@ -0,0 +1,168 @@
+// Verify a network without the window, e.g. in scripts and CI
+//
+// usage: sortingnetwork-verify [-v] [OPTIONS] [FILE]
+//
+// Reads a network in any format the app takes from FILE, or stdin if it is missing or "-", and
+// prints the answer of yukicoder No.3047. Exit code: 0 for a sorting network, 1 for No, 2 for an
+// error, 3 for the timeout.
+use sortingnetwork_core::sorting_network_check_v2::{
+    JobProgress, State, VerifyOptions, is_sorting_network_future_v2,
+};
+use sortingnetwork_core::sorting_network_parse::parse_timeout;
+use sortingnetwork_core::sorting_network_task::parse_task;
+use sortingnetwork_core::threadpool::ThreadPool;
+use std::io::Read;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USAGE: &str = "usage: sortingnetwork-verify [-v] [OPTIONS] [FILE]
+
+Verify the network in FILE (stdin if missing or \"-\") and print Yes or No, the count and the
+indices.
+
+  -v, --verbose           print the log of each job to stderr
+  --blocks BLOCKS         sorted input blocks, e.g. \"1-8 9-16\"
+  --target POSITIONS      output positions to verify, e.g. \"min max\", \"median\", \"1-4 16\"
+  --preconditions CLAUSES fixed or ordered inputs, e.g. \"3:0 2<=5 ksorted:4\"
+  --count                 also print the number of failing inputs
+  --memory-budget SIZE    stop when a job would need more, e.g. 512, 4G
+  --strategy NAME         order of combining the components (smallest-union by default)
+  --timeout T             give up after T, e.g. 90, 30s, 5m, 1h";
+
+#[derive(Default)]
+struct Args {
+    verbose: bool,
+    blocks: String,
+    target: String,
+    preconditions: String,
+    count: bool,
+    memory_budget: String,
+    strategy: String,
+    timeout: String,
+    path: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-v" | "--verbose" => args.verbose = true,
+            "--blocks" => args.blocks = it.next().ok_or("missing blocks")?,
+            "--target" => args.target = it.next().ok_or("missing target")?,
+            "--preconditions" => args.preconditions = it.next().ok_or("missing preconditions")?,
+            "--count" => args.count = true,
+            "--memory-budget" => args.memory_budget = it.next().ok_or("missing memory budget")?,
+            "--strategy" => args.strategy = it.next().ok_or("missing strategy")?,
+            "--timeout" => args.timeout = it.next().ok_or("missing timeout")?,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            "-" => args.path = None,
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {arg}")),
+            _ if args.path.is_some() => return Err(format!("unexpected argument: {arg}")),
+            _ => args.path = Some(arg),
+        }
+    }
+    Ok(args)
+}
+
+fn read_input(path: &Option<String>) -> Result<String, String> {
+    match path {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}")),
+        None => {
+            let mut net = String::new();
+            std::io::stdin()
+                .read_to_string(&mut net)
+                .map_err(|e| format!("stdin: {e}"))?;
+            Ok(net)
+        }
+    }
+}
+
+fn verify<S: State>(
+    n: usize,
+    cmp: Vec<(usize, usize)>,
+    options: VerifyOptions,
+    timeout: Option<Duration>,
+    verbose: bool,
+) -> Result<ExitCode, String> {
+    let pool = Arc::new(ThreadPool::new(
+        std::thread::available_parallelism()
+            .map(|x| x.get())
+            .unwrap_or(2),
+    ));
+    let targeted = !options.target.is_empty();
+    let mut future =
+        is_sorting_network_future_v2::<S>(pool, n, Arc::new(cmp), Arc::new(options), timeout);
+    let mut last_progress = None;
+    loop {
+        match future.recv_progress() {
+            Ok(JobProgress::Progress(progress)) => {
+                if verbose {
+                    eprintln!("{}", progress.log);
+                }
+                last_progress = Some(progress);
+            }
+            Ok(JobProgress::Log(log)) => {
+                if verbose {
+                    eprintln!("{log}");
+                }
+            }
+            Ok(JobProgress::Done) => break,
+            Ok(JobProgress::Error(e)) => return Err(e),
+            Ok(JobProgress::Cancel) => return Err("cancelled".to_string()),
+            Ok(JobProgress::Timeout(progress)) => {
+                if verbose {
+                    eprintln!("{}", progress.log);
+                }
+                eprintln!("timeout after {}ms", progress.time);
+                return Ok(ExitCode::from(3));
+            }
+            Err(_) => return Err("the verification ended without a result".to_string()),
+        }
+    }
+    let result = last_progress.ok_or("the verification ended without a result")?;
+    print!("{}", result.answer(targeted));
+    if let Some(count) = &result.failure_count {
+        println!("failing: {}/{}", count.failing, count.inputs);
+    }
+    Ok(ExitCode::from(if result.is_sorting_network() {
+        0
+    } else {
+        1
+    }))
+}
+
+fn run() -> Result<ExitCode, String> {
+    let args = parse_args()?;
+    let net = read_input(&args.path)?;
+    // The same checks as the app: the spans of a network error, the ranges of the options and
+    // the limits of the strategy and the count
+    let (n, _, cmp, options) = parse_task(
+        &net,
+        &args.blocks,
+        &args.target,
+        &args.preconditions,
+        args.count,
+        &args.memory_budget,
+        &args.strategy,
+    )
+    .map_err(|e| e.message)?;
+    let timeout = parse_timeout(&args.timeout)?;
+    // The u64 state covers up to 64 channels, wider networks use u128
+    if n <= u64::BITS as usize {
+        verify::<u64>(n, cmp, options, timeout, args.verbose)
+    } else {
+        verify::<u128>(n, cmp, options, timeout, args.verbose)
+    }
+}
+
+fn main() -> ExitCode {
+    run().unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        ExitCode::from(2)
+    })
+}
@@ -0,0 +1,13 @@
+// Sorting networks without the GUI: shared by the app, the frontend and the command-line tools
+pub mod sorting_network_check_brute;
+pub mod sorting_network_check_v2;
+pub mod sorting_network_export;
+pub mod sorting_network_gen;
+pub mod sorting_network_hdl;
+pub mod sorting_network_kernel;
+pub mod sorting_network_opts;
+pub mod sorting_network_parse;
+pub mod sorting_network_strategy;
+pub mod sorting_network_svg;
+pub mod sorting_network_task;
+pub mod threadpool;
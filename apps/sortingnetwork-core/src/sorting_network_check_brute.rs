@@ -0,0 +1,433 @@
+use crate::sorting_network_check_v2::{
+    FailureCount, JobResult, Precondition, State, VerifyOptions,
+};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+// Largest n checked by running all 2^n inputs
+pub const BRUTE_MAX_N: usize = 24;
+
+// Reference checker: apply the network to every 0-1 input satisfying the preconditions.
+// Fills `used`, `unsorted`, `wrong` and `failure_count` the same way as
+// `is_sorting_network_future_v2`.
+pub fn is_sorting_network_brute<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+) -> Result<JobResult<S>, String> {
+    enumerate_inputs(n, cmp, options, &AtomicBool::new(false))
+}
+
+// The same for the enumerate strategy of the verifier; a cancel leaves the tally incomplete
+pub(crate) fn enumerate_inputs<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+    cancel_state: &AtomicBool,
+) -> Result<JobResult<S>, String> {
+    if !(2..=BRUTE_MAX_N).contains(&n) {
+        return Err(format!("n = {n} is out of range 2..={BRUTE_MAX_N}"));
+    }
+    if let Some((i, &(a, b))) = cmp
+        .iter()
+        .enumerate()
+        .find(|&(_, &(a, b))| a >= b || b >= n)
+    {
+        return Err(format!(
+            "comparator {} = ({a}, {b}) is invalid for n = {n}",
+            i + 1
+        ));
+    }
+    if let Some(&p) = options.target.iter().find(|&&p| p >= n) {
+        return Err(format!("target position {p} is out of range for n = {n}"));
+    }
+    let clauses = options.clauses();
+    let channels = |&clause: &Precondition| match clause {
+        Precondition::Fixed(c, _) => c,
+        Precondition::LessEq(a, b) => a.max(b),
+    };
+    if let Some(c) = clauses.iter().map(channels).find(|&c| c >= n) {
+        return Err(format!(
+            "precondition channel {c} is out of range for n = {n}"
+        ));
+    }
+    let n_mask = (1u32 << n) - 1;
+    let target = if options.target.is_empty() {
+        n_mask
+    } else {
+        options.target.iter().fold(0u32, |m, &p| m | (1 << p))
+    };
+    let tally = (0..1u32 << n)
+        .into_par_iter()
+        .filter(|&x| {
+            !cancel_state.load(Relaxed)
+                && clauses.iter().all(|&clause| match clause {
+                    Precondition::Fixed(c, v) => (x >> c) & 1 == v as u32,
+                    Precondition::LessEq(a, b) => (x >> a) & 1 <= (x >> b) & 1,
+                })
+        })
+        .fold(
+            || Tally::new(n, cmp.len()),
+            |mut tally, x| {
+                let mut y = x;
+                for (i, &(a, b)) in cmp.iter().enumerate() {
+                    if (y >> a) & 1 == 1 && (y >> b) & 1 == 0 {
+                        tally.used[i] = true;
+                        y ^= (1 << a) | (1 << b);
+                    }
+                }
+                for (i, unsorted) in tally.unsorted.iter_mut().enumerate() {
+                    if (y >> i) & 1 == 1 {
+                        *unsorted |= !y & n_mask & (u32::MAX << (i + 1));
+                    }
+                }
+                let k = y.count_ones() as usize;
+                let wrong = y ^ (((1u32 << k) - 1) << (n - k));
+                tally.wrong |= wrong;
+                tally.count.inputs += 1;
+                tally.count.failing += (wrong & target != 0) as u128;
+                for (p, failing_at) in tally.count.failing_at.iter_mut().enumerate() {
+                    *failing_at += ((wrong >> p) & 1) as u128;
+                }
+                tally
+            },
+        )
+        .reduce(|| Tally::new(n, cmp.len()), Tally::merge);
+    let mut result = JobResult::<S>::new(n, cmp);
+    result.progress = 1 << n;
+    result.progress_all = 1 << n;
+    result.used = tally.used;
+    let to_state = |x: u32| {
+        (0..n)
+            .filter(|&i| (x >> i) & 1 == 1)
+            .fold(S::ZERO, |m, i| m | (S::ONE << i))
+    };
+    result.unsorted = tally.unsorted.into_iter().map(to_state).collect();
+    // Keep only the unsorted pairs touching a target position that can be wrong
+    if !options.target.is_empty() {
+        let wrong = target & tally.wrong;
+        for (i, unsorted) in result.unsorted.iter_mut().enumerate() {
+            if (wrong >> i) & 1 == 0 {
+                *unsorted &= to_state(wrong);
+            }
+        }
+        result.wrong = (0..n).filter(|&p| (wrong >> p) & 1 == 1).collect();
+    }
+    if options.count {
+        result.failure_count = Some(tally.count);
+    }
+    Ok(result)
+}
+
+struct Tally {
+    used: Vec<bool>,
+    unsorted: Vec<u32>,
+    wrong: u32,
+    count: FailureCount,
+}
+impl Tally {
+    fn new(n: usize, l: usize) -> Self {
+        Self {
+            used: vec![false; l],
+            unsorted: vec![0; n],
+            wrong: 0,
+            count: FailureCount {
+                inputs: 0,
+                failing: 0,
+                failing_at: vec![0; n],
+            },
+        }
+    }
+    fn merge(mut self, other: Self) -> Self {
+        self.used
+            .iter_mut()
+            .zip(other.used)
+            .for_each(|(u, v)| *u |= v);
+        self.unsorted
+            .iter_mut()
+            .zip(other.unsorted)
+            .for_each(|(u, v)| *u |= v);
+        self.wrong |= other.wrong;
+        self.count.inputs += other.count.inputs;
+        self.count.failing += other.count.failing;
+        self.count
+            .failing_at
+            .iter_mut()
+            .zip(other.count.failing_at)
+            .for_each(|(u, v)| *u += v);
+        self
+    }
+}
+
+// Helpers of the differential tests, shared with the tests of the other modules
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use crate::sorting_network_check_v2::{
+        JobProgress, apply_network, is_sorting_network_future_v2,
+    };
+    use crate::threadpool::ThreadPool;
+    use std::sync::Arc;
+
+    pub(crate) struct XorShift(pub u64);
+    impl XorShift {
+        pub(crate) fn next(&mut self, m: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % m as u64) as usize
+        }
+    }
+
+    pub(crate) fn verify<S: State>(
+        n: usize,
+        cmp: &[(usize, usize)],
+        options: &VerifyOptions,
+    ) -> JobResult<S> {
+        let pool = Arc::new(ThreadPool::new(1));
+        let mut future = is_sorting_network_future_v2::<S>(
+            pool,
+            n,
+            Arc::new(cmp.to_vec()),
+            Arc::new(options.clone()),
+            None,
+        );
+        let mut last = None;
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Progress(progress) => last = Some(progress),
+                JobProgress::Log(_) => {}
+                JobProgress::Cancel => panic!("cancelled"),
+                JobProgress::Error(e) => panic!("{e}"),
+                JobProgress::Timeout(_) => panic!("timeout"),
+                JobProgress::Done => break,
+            }
+        }
+        last.unwrap()
+    }
+
+    pub(crate) fn assert_agree<S: State>(
+        n: usize,
+        cmp: &[(usize, usize)],
+        options: &VerifyOptions,
+    ) {
+        let result = verify::<S>(n, cmp, options);
+        let expected = is_sorting_network_brute::<S>(n, cmp, options).unwrap();
+        let case = format!("n: {n}, cmp: {cmp:?}, options: {options:?}");
+        assert_eq!(result.used, expected.used, "used, {case}");
+        assert_eq!(result.unsorted, expected.unsorted, "unsorted, {case}");
+        assert_eq!(result.wrong, expected.wrong, "wrong, {case}");
+        assert_eq!(
+            result.failure_count, expected.failure_count,
+            "failure_count, {case}"
+        );
+        assert_eq!(
+            result.counterexample.is_some(),
+            !expected.is_sorting_network(),
+            "counterexample, {case}"
+        );
+        if let Some(c) = result.counterexample {
+            let input = c
+                .input
+                .iter()
+                .enumerate()
+                .filter(|&(_, &x)| x != 0)
+                .fold(S::ZERO, |m, (i, _)| m | (S::ONE << i));
+            let output = apply_network(cmp, input);
+            assert!(
+                (0..n).all(|i| (c.output[i] != 0) == ((output >> i) & S::ONE != S::ZERO)),
+                "counterexample output, {case}"
+            );
+        }
+    }
+
+    pub(crate) fn counting() -> VerifyOptions {
+        VerifyOptions {
+            count: true,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn random_network(rng: &mut XorShift, n: usize, m: usize) -> Vec<(usize, usize)> {
+        (0..m)
+            .map(|_| {
+                let a = rng.next(n);
+                let b = (a + 1 + rng.next(n - 1)) % n;
+                (a.min(b), a.max(b))
+            })
+            .collect()
+    }
+
+    // Drop one comparator so that most sorting networks fail
+    pub(crate) fn without_one(rng: &mut XorShift, cmp: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let k = rng.next(cmp.len());
+        [&cmp[..k], &cmp[k + 1..]].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+    use crate::sorting_network_check_v2::{Checkpoint, JobProgress, verify_on_current_thread};
+    use crate::sorting_network_gen::*;
+    use crate::sorting_network_strategy::STRATEGIES;
+
+    #[test]
+    fn random_networks() {
+        let mut rng = XorShift(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let n = 2 + rng.next(11);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            assert_agree::<u64>(n, &cmp, &counting());
+        }
+    }
+
+    #[test]
+    fn random_networks_with_options() {
+        let mut rng = XorShift(0x9e3779b97f4a7c15);
+        for _ in 0..500 {
+            let n = 2 + rng.next(11);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            let mut options = VerifyOptions {
+                count: true,
+                ..Default::default()
+            };
+            let mut l = 0;
+            while l < n {
+                let r = (l + 1 + rng.next(5)).min(n);
+                if rng.next(2) == 0 {
+                    options.sorted_blocks.push((l, r));
+                }
+                l = r;
+            }
+            if rng.next(2) == 0 {
+                options.target = (0..n).filter(|_| rng.next(3) == 0).collect();
+            }
+            for _ in 0..rng.next(4) {
+                let (a, b) = (rng.next(n), rng.next(n));
+                if rng.next(3) == 0 {
+                    options
+                        .preconditions
+                        .push(Precondition::Fixed(a, rng.next(2) == 0));
+                } else if a != b {
+                    options.preconditions.push(Precondition::LessEq(a, b));
+                }
+            }
+            if options.is_satisfiable(n) {
+                assert_agree::<u64>(n, &cmp, &options);
+            }
+        }
+    }
+
+    #[test]
+    fn strategies() {
+        let mut rng = XorShift(0xbf58476d1ce4e5b9);
+        for _ in 0..200 {
+            let n = 2 + rng.next(11);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            for strategy in STRATEGIES {
+                let mut options = counting();
+                options.strategy = strategy.name().to_string();
+                if rng.next(2) == 0 {
+                    options.sorted_blocks.push((0, n / 2));
+                }
+                assert_agree::<u64>(n, &cmp, &options);
+                assert_eq!(verify::<u64>(n, &cmp, &options).strategy, strategy.name());
+            }
+        }
+    }
+
+    // The browser runs the jobs without the pool, the results are the same
+    #[test]
+    fn current_thread() {
+        let mut rng = XorShift(0xbf58476d1ce4e5b9);
+        for _ in 0..100 {
+            let n = 2 + rng.next(11);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            let expected = verify::<u64>(n, &cmp, &counting());
+            let last = std::cell::RefCell::new(None);
+            let done = std::cell::Cell::new(false);
+            let checkpoint = Checkpoint::<u64>::new(n, &cmp, &counting());
+            verify_on_current_thread(checkpoint, None, |progress| match progress {
+                JobProgress::Progress(progress) => *last.borrow_mut() = Some(progress),
+                JobProgress::Log(_) => {}
+                JobProgress::Done => done.set(true),
+                JobProgress::Cancel => panic!("cancelled"),
+                JobProgress::Error(e) => panic!("{e}"),
+                JobProgress::Timeout(_) => panic!("timeout"),
+            });
+            assert!(done.get());
+            let result = last.into_inner().unwrap();
+            let case = format!("n: {n}, cmp: {cmp:?}");
+            assert_eq!(result.used, expected.used, "used, {case}");
+            assert_eq!(result.unsorted, expected.unsorted, "unsorted, {case}");
+            assert_eq!(result.wrong, expected.wrong, "wrong, {case}");
+            assert_eq!(
+                result.failure_count, expected.failure_count,
+                "failure_count, {case}"
+            );
+        }
+    }
+
+    type Generator = fn(usize) -> Vec<(usize, usize)>;
+
+    #[test]
+    fn generators() {
+        let mut rng = XorShift(0xd1b54a32d192ed03);
+        let generators: [Generator; 6] = [
+            gen_bubble_max,
+            gen_bubble_min,
+            gen_oddeven,
+            gen_bitonic,
+            gen_batcher,
+            gen_pairwise,
+        ];
+        for n in 2..=16 {
+            for generator in generators {
+                let cmp = generator(n);
+                assert_agree::<u64>(n, &cmp, &counting());
+                assert_agree::<u128>(n, &without_one(&mut rng, &cmp), &counting());
+            }
+        }
+    }
+
+    // Input out of range is an error, not a panic
+    #[test]
+    fn invalid_input() {
+        let check = |n, cmp: &[(usize, usize)], options: &VerifyOptions| {
+            is_sorting_network_brute::<u64>(n, cmp, options).unwrap_err()
+        };
+        let none = VerifyOptions::default();
+        assert_eq!(check(1, &[], &none), "n = 1 is out of range 2..=24");
+        assert_eq!(check(25, &[], &none), "n = 25 is out of range 2..=24");
+        assert_eq!(
+            check(3, &[(0, 1), (2, 1)], &none),
+            "comparator 2 = (2, 1) is invalid for n = 3"
+        );
+        assert_eq!(
+            check(3, &[(0, 3)], &none),
+            "comparator 1 = (0, 3) is invalid for n = 3"
+        );
+        let target = VerifyOptions {
+            target: vec![3],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(3, &[], &target),
+            "target position 3 is out of range for n = 3"
+        );
+        let blocks = VerifyOptions {
+            sorted_blocks: vec![(0, 4)],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(3, &[], &blocks),
+            "precondition channel 3 is out of range for n = 3"
+        );
+    }
+}
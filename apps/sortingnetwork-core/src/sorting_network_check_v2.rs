@@ -0,0 +1,2699 @@
+use crate::sorting_network_check_brute::enumerate_inputs;
+use crate::sorting_network_strategy::{
+    CombineCandidate, STRATEGIES, VerifyStrategy, strategy_by_name,
+};
+use crate::threadpool;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr, Sub,
+};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// std::time::Instant panics on wasm32-unknown-unknown, the clock of the browser stands in
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy)]
+struct Instant(f64);
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    fn now() -> Self {
+        Instant(js_sys::Date::now())
+    }
+    fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((js_sys::Date::now() - self.0).max(0.0) / 1000.0)
+    }
+}
+
+// Bitset of channels; the width is chosen from n (u64 up to 64 channels, u128 up to 128)
+pub trait State:
+    Copy
+    + Ord
+    + Default
+    + Send
+    + Sync
+    + std::fmt::Debug
+    + std::hash::Hash
+    + 'static
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+    + Sub<Output = Self>
+    + BitAndAssign
+    + BitOrAssign
+    + BitXorAssign
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+    fn trailing_zeros(self) -> u32;
+    fn count_ones(self) -> u32;
+    fn is_power_of_two(self) -> bool;
+    fn to_u128(self) -> u128;
+    fn from_u128(x: u128) -> Self;
+}
+macro_rules! impl_state {
+    ($($t:ty),*) => {
+        $(
+            impl State for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$t>::MAX;
+                #[inline(always)]
+                fn trailing_zeros(self) -> u32 {
+                    <$t>::trailing_zeros(self)
+                }
+                #[inline(always)]
+                fn count_ones(self) -> u32 {
+                    <$t>::count_ones(self)
+                }
+                #[inline(always)]
+                fn is_power_of_two(self) -> bool {
+                    <$t>::is_power_of_two(self)
+                }
+                #[inline(always)]
+                fn to_u128(self) -> u128 {
+                    self as u128
+                }
+                #[inline(always)]
+                fn from_u128(x: u128) -> Self {
+                    x as $t
+                }
+            }
+        )*
+    };
+}
+impl_state!(u64, u128);
+
+// Maximum number of channels supported by the widest State
+pub const MAX_N: usize = u128::BITS as usize;
+
+#[derive(Debug, Clone)]
+pub struct JobResult<S: State> {
+    pub time: u64,
+    // Number of branches examined: 0 <= branches <= FIB1[n]
+    pub progress: u64,
+    pub progress_all: u64,
+    // Whether comparator i was used
+    pub used: Vec<bool>,
+    // Bitmap of positions where z, o are not sorted
+    pub unsorted: Vec<S>,
+    // An input left unsorted by the network (available after finishing)
+    pub counterexample: Option<Counterexample>,
+    // Minimal input for each unsorted pair (i, j) (available after finishing)
+    pub witnesses: Vec<Witness>,
+    // Target positions that can get a wrong value (available after finishing)
+    pub wrong: Vec<usize>,
+    // Exact number of failing inputs (available after finishing, if requested)
+    pub failure_count: Option<FailureCount>,
+    // Name of the VerifyStrategy
+    pub strategy: &'static str,
+    pub log: String,
+}
+
+// An unsorted pair (i, j) and its minimal input
+pub type Witness = ((usize, usize), Counterexample);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Counterexample {
+    // 0-1 input vector
+    pub input: Vec<u8>,
+    // 0-1 output vector of the network for `input` (not sorted)
+    pub output: Vec<u8>,
+    // Permutation of 1..=n that is left unsorted as well
+    pub permutation: Vec<usize>,
+}
+impl Counterexample {
+    pub fn new<S: State>(n: usize, input: S, output: S) -> Self {
+        let bits = |x: S| {
+            (0..n)
+                .map(|i| ((x >> i) & S::ONE != S::ZERO) as u8)
+                .collect::<Vec<_>>()
+        };
+        let (input, output) = (bits(input), bits(output));
+        // 0-1 principle: zeros take the smaller values, ones the larger ones
+        let zeros = input.iter().filter(|&&x| x == 0).count();
+        let (mut next_zero, mut next_one) = (1, zeros + 1);
+        let permutation = input
+            .iter()
+            .map(|&x| {
+                let v = if x == 0 {
+                    &mut next_zero
+                } else {
+                    &mut next_one
+                };
+                *v += 1;
+                *v - 1
+            })
+            .collect();
+        Self {
+            input,
+            output,
+            permutation,
+        }
+    }
+}
+
+// Number of 0-1 inputs (satisfying the preconditions) that come out wrong
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FailureCount {
+    pub inputs: u128,
+    // Inputs leaving some target position (any position by default) wrong
+    pub failing: u128,
+    // Inputs leaving output position p wrong
+    pub failing_at: Vec<u128>,
+}
+
+#[derive(Debug, Clone)]
+pub enum JobProgress<S: State> {
+    Progress(JobResult<S>),
+    Log(String),
+    Done,
+    Cancel,
+    // Stopped before finishing, the last progress holds the partial results
+    Error(String),
+    // Stopped by the timeout, with the results of the jobs completed so far
+    Timeout(JobResult<S>),
+}
+
+impl<S: State> JobResult<S> {
+    pub fn new(n: usize, cmp: &[(usize, usize)]) -> Self {
+        Self {
+            time: 0,
+            progress: 0,
+            progress_all: u64::MAX,
+            used: vec![false; cmp.len()],
+            unsorted: vec![S::ZERO; n],
+            counterexample: None,
+            witnesses: vec![],
+            wrong: vec![],
+            failure_count: None,
+            strategy: "",
+            log: String::new(),
+        }
+    }
+    // Get unused comparators
+    pub fn get_unused(&self) -> Vec<bool> {
+        self.used.iter().map(|&u| !u).collect()
+    }
+    // Check if it is a sorting network
+    pub fn is_sorting_network(&self) -> bool {
+        self.unsorted.iter().all(|&f| f == S::ZERO)
+    }
+    // Get bitmap of positions where sorting is not done
+    pub fn get_unsorted_bitmap(&self) -> Vec<S> {
+        self.unsorted.clone()
+    }
+    // Get bitmap of positions where sorting is not done, expanded to n x n booleans
+    pub fn get_unsorted_matrix(&self) -> Vec<Vec<bool>> {
+        let n = self.unsorted.len();
+        self.unsorted
+            .iter()
+            .map(|&x| (0..n).map(|j| (x >> j) & S::ONE != S::ZERO).collect())
+            .collect()
+    }
+    // Get all pairs of positions where sorting is not done
+    pub fn get_unsorted_allpairs(&self) -> Vec<(usize, usize)> {
+        let mut unsorted = vec![];
+        for (i, &u) in self.unsorted.iter().enumerate() {
+            let mut z = u;
+            while z != S::ZERO {
+                let j = z.trailing_zeros() as usize;
+                unsorted.push((i, j));
+                z &= z - S::ONE;
+            }
+        }
+        unsorted
+    }
+    // Get positions where sorting is not done
+    pub fn get_unsorted_adjacent(&self) -> Vec<usize> {
+        let mut unsorted = vec![];
+        for i in 0..self.unsorted.len().saturating_sub(1) {
+            if ((self.unsorted[i] >> i) & (S::ONE << 1)) != S::ZERO {
+                unsorted.push(i);
+            }
+        }
+        unsorted
+    }
+    // Answer of yukicoder No.3047: Yes and the unused comparators, or No and the unsorted
+    // adjacent pairs (the wrong target positions for a selection network), all 1-based
+    pub fn answer(&self, targeted: bool) -> String {
+        let (yes_no, indexes) = if self.is_sorting_network() {
+            let unused = (0..self.used.len()).filter(|&i| !self.used[i]);
+            ("Yes", unused.map(|i| i + 1).collect::<Vec<_>>())
+        } else if targeted {
+            ("No", self.wrong.iter().map(|&p| p + 1).collect())
+        } else {
+            (
+                "No",
+                self.get_unsorted_adjacent()
+                    .iter()
+                    .map(|&i| i + 1)
+                    .collect(),
+            )
+        };
+        format!(
+            "{yes_no}\n{len}\n{indexes}\n",
+            len = indexes.len(),
+            indexes = indexes
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+}
+pub struct JobResultFuture<S: State> {
+    progress_rx: mpsc::Receiver<JobProgress<S>>,
+    cancel_state: Arc<AtomicBool>,
+    checkpoint_state: Arc<Mutex<Option<PathBuf>>>,
+}
+impl<S: State> JobResultFuture<S> {
+    pub fn recv_progress(&mut self) -> Result<JobProgress<S>, mpsc::RecvError> {
+        self.progress_rx.recv()
+    }
+    pub fn try_recv_progress(&mut self) -> Result<JobProgress<S>, mpsc::TryRecvError> {
+        self.progress_rx.try_recv()
+    }
+    // Wait at most `timeout`, so that the caller can still cancel a long job
+    pub fn recv_progress_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<JobProgress<S>, mpsc::RecvTimeoutError> {
+        self.progress_rx.recv_timeout(timeout)
+    }
+    // The worker stops within a few states per thread, or one pass when sorting
+    pub fn cancel(&mut self) {
+        self.cancel_state.store(true, Relaxed);
+    }
+    // Write a checkpoint to `path` once the running job is completed
+    pub fn checkpoint(&mut self, path: PathBuf) {
+        *self.checkpoint_state.lock().unwrap() = Some(path);
+    }
+}
+// Nobody is waiting for the result any more
+impl<S: State> Drop for JobResultFuture<S> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+// par_sort_unstable that gives up once `cancel_state` is set: from then on no element is less
+// than another (only Less matters to the sort), so it winds down in about one pass over `v`
+// and leaves it in some order
+fn par_sort_cancellable<T: Send>(
+    v: &mut [T],
+    cancel_state: &AtomicBool,
+    is_less: impl Fn(&T, &T) -> bool + Sync,
+) {
+    v.par_sort_unstable_by(|a, b| {
+        if !cancel_state.load(Relaxed) && is_less(a, b) {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Greater
+        }
+    });
+}
+
+// Fibonacci numbers: FIB1[0] = 1, FIB1[1] = 1, FIB1[i] = FIB1[i-1] + FIB1[i-2] (2 <= i <= u64::BITS)
+pub const FIB1: [u64; (u64::BITS + 1) as usize] = {
+    let mut fib = [1; (u64::BITS + 1) as usize];
+    let mut i = 2;
+    while i <= u64::BITS as usize {
+        fib[i] = fib[i - 1] + fib[i - 2];
+        i += 1;
+    }
+    fib
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum DsuBySizeElement {
+    Size(usize),
+    Parent(usize),
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// Disjoint Set Union (Union-Find) by Size
+pub struct DsuBySize(Vec<DsuBySizeElement>);
+impl DsuBySize {
+    pub fn new(n: usize) -> Self {
+        Self((0..n).map(|_| DsuBySizeElement::Size(1)).collect())
+    }
+    pub fn root_size(&mut self, u: usize) -> (usize, usize) {
+        match self.0[u] {
+            DsuBySizeElement::Size(size) => (u, size),
+            DsuBySizeElement::Parent(v) if u == v => (u, 1),
+            DsuBySizeElement::Parent(v) => {
+                let (root, size) = self.root_size(v);
+                self.0[u] = DsuBySizeElement::Parent(root);
+                (root, size)
+            }
+        }
+    }
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        let (u, size_u) = self.root_size(u);
+        let (v, size_v) = self.root_size(v);
+        if u == v {
+            return false;
+        }
+        if size_u < size_v {
+            self.0[u] = DsuBySizeElement::Parent(v);
+            self.0[v] = DsuBySizeElement::Size(size_u + size_v);
+        } else {
+            self.0[v] = DsuBySizeElement::Parent(u);
+            self.0[u] = DsuBySizeElement::Size(size_u + size_v);
+        }
+        true
+    }
+    pub fn root(&mut self, u: usize) -> usize {
+        self.root_size(u).0
+    }
+    pub fn size(&mut self, u: usize) -> usize {
+        self.root_size(u).1
+    }
+    pub fn equiv(&mut self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct CeEntry {
+    cei: usize,
+    a: usize,
+    b: usize,
+}
+impl std::fmt::Debug for CeEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.cei, self.a, self.b)
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum VerifyJob {
+    Cmp {
+        root: usize,
+        cmp_part: Vec<CeEntry>,
+    },
+    Combine {
+        root_master: usize,
+        root_slave: usize,
+    },
+}
+
+// Input precondition clause (0-based channels)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Precondition {
+    // The channel always gets the given value
+    Fixed(usize, bool),
+    // The first channel never gets a larger value than the second
+    LessEq(usize, usize),
+}
+
+// Settings of a verification besides the network itself
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifyOptions {
+    // Input blocks [l, r) known to be sorted (merging networks)
+    pub sorted_blocks: Vec<(usize, usize)>,
+    // Output positions that must be correct, all of them if empty (selection networks)
+    pub target: Vec<usize>,
+    // Clauses every input satisfies
+    pub preconditions: Vec<Precondition>,
+    // Count the failing inputs exactly after verifying
+    pub count: bool,
+    // Stop before a job whose states would take more bytes than this
+    pub memory_budget: Option<usize>,
+    // Name of the VerifyStrategy, the default one if empty
+    pub strategy: String,
+}
+impl VerifyOptions {
+    pub fn strategy(&self) -> &'static dyn VerifyStrategy {
+        strategy_by_name(&self.strategy).unwrap_or(STRATEGIES[0])
+    }
+    // Preconditions together with the sorted blocks
+    pub fn clauses(&self) -> Vec<Precondition> {
+        let mut clauses = self.preconditions.clone();
+        for &(l, r) in self.sorted_blocks.iter() {
+            clauses.extend((l + 1..r).map(|u| Precondition::LessEq(u - 1, u)));
+        }
+        clauses
+    }
+    // Whether some input satisfies all clauses: propagate the fixed values along the orderings
+    pub fn is_satisfiable(&self, n: usize) -> bool {
+        let clauses = self.clauses();
+        let mut value = vec![None; n];
+        for &clause in clauses.iter() {
+            if let Precondition::Fixed(c, v) = clause {
+                if value[c].is_some_and(|x| x != v) {
+                    return false;
+                }
+                value[c] = Some(v);
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &clause in clauses.iter() {
+                if let Precondition::LessEq(a, b) = clause {
+                    match (value[a], value[b]) {
+                        (Some(true), Some(false)) => return false,
+                        (Some(true), None) => value[b] = Some(true),
+                        (None, Some(false)) => value[a] = Some(false),
+                        _ => continue,
+                    }
+                    changed = true;
+                }
+            }
+        }
+        true
+    }
+}
+
+// Channel components and the states of each root
+type Components<T> = (DsuBySize, Vec<Vec<T>>);
+
+// Components and their (z, o) states before the first comparator.
+// Channels ordered by a clause form one component, which is split into disjoint subcubes
+// on the first violated ordering a <= b: either a = 0, or a = b = 1.
+fn initial_states<S: State>(n: usize, options: &VerifyOptions) -> Components<(S, S)> {
+    let clauses = options.clauses();
+    let mut dsu = DsuBySize::new(n);
+    for &clause in clauses.iter() {
+        if let Precondition::LessEq(a, b) = clause {
+            debug_assert!(a < n && b < n);
+            dsu.unite(a, b);
+        }
+    }
+    let roots = (0..n).map(|u| dsu.root(u)).collect::<Vec<_>>();
+    let mut states = vec![vec![]; n];
+    for root in (0..n).filter(|&u| roots[u] == u) {
+        let comp_mask = (0..n)
+            .filter(|&u| roots[u] == root)
+            .fold(S::ZERO, |m, u| m | (S::ONE << u));
+        let (mut z, mut o) = (comp_mask, comp_mask);
+        let mut orders = vec![];
+        for &clause in clauses.iter() {
+            match clause {
+                Precondition::Fixed(c, v) if roots[c] == root => {
+                    if v {
+                        z &= !(S::ONE << c);
+                    } else {
+                        o &= !(S::ONE << c);
+                    }
+                }
+                Precondition::LessEq(a, b) if roots[a] == root => {
+                    orders.push((S::ONE << a, S::ONE << b))
+                }
+                _ => {}
+            }
+        }
+        let mut stack = vec![(z, o)];
+        while let Some((z, o)) = stack.pop() {
+            if z | o != comp_mask {
+                continue;
+            }
+            match orders
+                .iter()
+                .find(|&&(ma, mb)| o & ma != S::ZERO && z & mb != S::ZERO)
+            {
+                Some(&(ma, mb)) => {
+                    stack.push((z & !ma & !mb, o));
+                    stack.push((z, o & !ma));
+                }
+                None => states[root].push((z, o)),
+            }
+        }
+    }
+    (dsu, states)
+}
+
+// Splits the network into jobs one at a time, so that the strategy sees the states so far:
+// the comparators layer by layer within the components, and when none can be applied, the
+// combination of components picked by the strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Planner {
+    dsu: DsuBySize,
+    cmp_layered: Vec<bool>,
+    cmp_skip: usize,
+    pending: VecDeque<VerifyJob>,
+}
+impl Planner {
+    fn new(cmp: &[(usize, usize)], dsu: DsuBySize) -> Self {
+        Self {
+            dsu,
+            cmp_layered: vec![false; cmp.len()],
+            cmp_skip: 0,
+            pending: VecDeque::new(),
+        }
+    }
+    // `lens` holds the number of states of each root
+    fn next_job(
+        &mut self,
+        n: usize,
+        cmp: &[(usize, usize)],
+        lens: &[usize],
+        strategy: &dyn VerifyStrategy,
+    ) -> Option<VerifyJob> {
+        debug_assert!((2..=MAX_N).contains(&n));
+        debug_assert!(cmp.iter().all(|&(a, b)| a < b && b < n));
+        if let Some(job) = self.pending.pop_front() {
+            return Some(job);
+        }
+        let dsu = &mut self.dsu;
+        let mut candidates = vec![];
+        let candidate = |dsu: &mut DsuBySize, a: usize, b: usize| {
+            let (root_a, size_a) = dsu.root_size(a);
+            let (root_b, size_b) = dsu.root_size(b);
+            CombineCandidate {
+                root_a,
+                root_b,
+                size_a,
+                size_b,
+                len_a: lens[root_a],
+                len_b: lens[root_b],
+                constraints: 0,
+            }
+        };
+        if strategy.combine_first()
+            && let Some(u) = (1..n).find(|&u| !dsu.equiv(0, u))
+        {
+            let c = candidate(dsu, 0, u);
+            return Some(self.combine(&c));
+        }
+        if self.cmp_skip >= cmp.len() {
+            return None;
+        }
+        let mut node_avail = u128::MAX >> (u128::BITS as usize - n);
+        let mut layer = (0..n).map(|_i| Vec::<CeEntry>::new()).collect::<Vec<_>>();
+        for (i, &(a, b)) in cmp.iter().enumerate().skip(self.cmp_skip) {
+            if node_avail.count_ones() < 2 {
+                break;
+            }
+            if self.cmp_layered[i] {
+                continue;
+            }
+            let node_unavail = (node_avail >> a) & (node_avail >> b) & 1 == 0;
+            node_avail &= !(1 << a) & !(1 << b);
+            if node_unavail {
+                continue;
+            }
+            if dsu.equiv(a, b) {
+                let root_a = dsu.root(a);
+                layer[root_a].push(CeEntry { cei: i, a, b });
+                self.cmp_layered[i] = true;
+            } else {
+                candidates.push(candidate(dsu, a, b));
+            }
+        }
+        if layer.iter().all(|v| v.is_empty()) {
+            // Combine
+            for c in candidates.iter_mut() {
+                let roots = (c.root_a.min(c.root_b), c.root_a.max(c.root_b));
+                c.constraints = (self.cmp_skip..cmp.len())
+                    .filter(|&i| !self.cmp_layered[i])
+                    .filter(|&i| {
+                        let (ra, rb) = (dsu.root(cmp[i].0), dsu.root(cmp[i].1));
+                        (ra.min(rb), ra.max(rb)) == roots
+                    })
+                    .count();
+            }
+            let c = candidates[strategy.pick(&candidates)];
+            Some(self.combine(&c))
+        } else {
+            // Comparator
+            for (root, ces) in layer.into_iter().enumerate().filter(|(_, v)| !v.is_empty()) {
+                self.pending.push_back(VerifyJob::Cmp {
+                    root,
+                    cmp_part: ces,
+                });
+            }
+            self.cmp_skip += self
+                .cmp_layered
+                .iter()
+                .skip(self.cmp_skip)
+                .take_while(|&&f| f)
+                .count();
+            self.pending.pop_front()
+        }
+    }
+    fn combine(&mut self, c: &CombineCandidate) -> VerifyJob {
+        let unite_result = self.dsu.unite(c.root_a, c.root_b);
+        debug_assert!(unite_result);
+        let root_master = self.dsu.root(c.root_a);
+        let root_slave = c.root_a ^ c.root_b ^ root_master;
+        VerifyJob::Combine {
+            root_master,
+            root_slave,
+        }
+    }
+}
+
+fn lens<T>(states: &[Vec<T>]) -> Vec<usize> {
+    states.iter().map(|v| v.len()).collect()
+}
+
+// The passes over the network: the verification, and the witness and count passes that follow
+// it. They run the same jobs, but the later ones branch more and keep more per state.
+#[derive(Clone, Copy, Debug)]
+enum Pass {
+    Verify,
+    Witness,
+    Count,
+}
+impl Pass {
+    fn name(self) -> &'static str {
+        match self {
+            Pass::Verify => "verify",
+            Pass::Witness => "witness",
+            Pass::Count => "count",
+        }
+    }
+    // Most states a comparator splits a state into: the verification splits one with both
+    // inputs free, the witnesses one with either input free that may be swapped, and the count
+    // one with both inputs free three ways
+    fn branches<S: State>(self, z: S, o: S, a: usize, b: usize) -> u128 {
+        let (ma, mb) = (S::ONE << a, S::ONE << b);
+        let free = |m: S| z & o & m != S::ZERO;
+        let swappable = o & ma != S::ZERO && z & mb != S::ZERO;
+        match self {
+            Pass::Verify if free(ma) && free(mb) => 2,
+            Pass::Witness if swappable && (free(ma) || free(mb)) => 2,
+            Pass::Count if free(ma) && free(mb) => 3,
+            _ => 1,
+        }
+    }
+}
+
+// Bytes held at the peak of a job: a Cmp job splits each state on the comparators of the job
+// (they share no channel), and the sorted states are extended in place, so they can be held
+// twice; a Combine job keeps both sides next to their product.
+fn job_memory<S: State, T: Sync>(
+    pass: Pass,
+    job: &VerifyJob,
+    states: &[Vec<T>],
+    zo: impl Fn(&T) -> (S, S) + Sync,
+) -> usize {
+    let all = states.iter().map(|v| v.len()).sum::<usize>();
+    let peak = match job {
+        VerifyJob::Cmp { root, cmp_part } => {
+            let generated = states[*root]
+                .par_iter()
+                .map(|t| {
+                    let (z, o) = zo(t);
+                    cmp_part
+                        .iter()
+                        .map(|&CeEntry { a, b, .. }| pass.branches(z, o, a, b))
+                        .fold(1u128, u128::saturating_mul)
+                })
+                .reduce(|| 0, u128::saturating_add);
+            (all as u128).saturating_add(generated.saturating_mul(2))
+        }
+        VerifyJob::Combine {
+            root_master,
+            root_slave,
+        } => {
+            let product = states[*root_master].len() as u128 * states[*root_slave].len() as u128;
+            (all as u128).saturating_add(product)
+        }
+    };
+    peak.saturating_mul(std::mem::size_of::<T>() as u128)
+        .try_into()
+        .unwrap_or(usize::MAX)
+}
+
+// Stop a pass before a job whose forecast exceeds the memory budget
+fn check_budget<S: State, T: Sync>(
+    options: &VerifyOptions,
+    pass: Pass,
+    job_index: usize,
+    job: &VerifyJob,
+    states: &[Vec<T>],
+    zo: impl Fn(&T) -> (S, S) + Sync,
+) -> Result<(), String> {
+    let Some(budget) = options.memory_budget else {
+        return Ok(());
+    };
+    let forecast = job_memory(pass, job, states, zo);
+    if forecast <= budget {
+        return Ok(());
+    }
+    Err(format!(
+        "Memory budget exceeded, pass: {pass}, job: {job_index}, forecast: {forecast}, budget: {budget}",
+        pass = pass.name(),
+        forecast = format_bytes(forecast),
+        budget = format_bytes(budget),
+    ))
+}
+
+// Bytes in the largest binary unit that keeps the number at least 1, e.g. 512B or 1.5GiB
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1 << 10 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
+
+// Everything needed to resume a verification, except the states
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointHeader {
+    pub n: usize,
+    // Width of the State the states were written with
+    pub bits: u32,
+    pub cmp: Vec<(usize, usize)>,
+    pub options: VerifyOptions,
+    planner: Planner,
+    // Number of jobs executed
+    pub job: usize,
+    dsu: DsuBySize,
+    pub used_cmp: Vec<bool>,
+    pub checked_cmp: Vec<bool>,
+    // Elapsed time before the checkpoint
+    pub time: u64,
+    // Number of states of each root, written after the header
+    lens: Vec<usize>,
+}
+
+// A verification between two jobs.
+// File format: the header as one JSON line, then the (z, o) states of each root as
+// little-endian integers of `bits` width.
+pub struct Checkpoint<S: State> {
+    pub header: CheckpointHeader,
+    states: Vec<Vec<(S, S)>>,
+}
+impl<S: State> Checkpoint<S> {
+    // Before the first job
+    pub fn new(n: usize, cmp: &[(usize, usize)], options: &VerifyOptions) -> Self {
+        let (dsu, states) = initial_states::<S>(n, options);
+        Self {
+            header: CheckpointHeader {
+                n,
+                bits: S::BITS,
+                cmp: cmp.to_vec(),
+                options: options.clone(),
+                planner: Planner::new(cmp, dsu.clone()),
+                job: 0,
+                dsu,
+                used_cmp: vec![false; cmp.len()],
+                checked_cmp: vec![false; cmp.len()],
+                time: 0,
+                lens: vec![],
+            },
+            states,
+        }
+    }
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let header = CheckpointHeader {
+            lens: self.states.iter().map(|v| v.len()).collect(),
+            ..self.header.clone()
+        };
+        let file = std::fs::File::create(path).map_err(|e| format!("checkpoint: {e}"))?;
+        let mut w = BufWriter::new(file);
+        let bytes = (S::BITS / 8) as usize;
+        serde_json::to_writer(&mut w, &header).map_err(|e| format!("checkpoint: {e}"))?;
+        w.write_all(b"\n").map_err(|e| format!("checkpoint: {e}"))?;
+        for &(z, o) in self.states.iter().flatten() {
+            w.write_all(&z.to_u128().to_le_bytes()[..bytes])
+                .and_then(|_| w.write_all(&o.to_u128().to_le_bytes()[..bytes]))
+                .map_err(|e| format!("checkpoint: {e}"))?;
+        }
+        w.flush().map_err(|e| format!("checkpoint: {e}"))
+    }
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| format!("checkpoint: {e}"))?;
+        let mut r = BufReader::new(file);
+        let header = read_checkpoint_header(&mut r)?;
+        if header.bits != S::BITS {
+            return Err(format!("checkpoint: {} bit states expected", S::BITS));
+        }
+        let bytes = (S::BITS / 8) as usize;
+        let mut buf = [0u8; 16];
+        let mut read = || {
+            r.read_exact(&mut buf[..bytes])
+                .map(|_| S::from_u128(u128::from_le_bytes(buf)))
+                .map_err(|e| format!("checkpoint: {e}"))
+        };
+        let mut states = vec![];
+        for &len in header.lens.iter() {
+            states.push(
+                (0..len)
+                    .map(|_| Ok((read()?, read()?)))
+                    .collect::<Result<Vec<_>, String>>()?,
+            );
+        }
+        Ok(Self { header, states })
+    }
+}
+
+fn read_checkpoint_header(r: &mut impl BufRead) -> Result<CheckpointHeader, String> {
+    let mut line = String::new();
+    r.read_line(&mut line)
+        .map_err(|e| format!("checkpoint: {e}"))?;
+    let header: CheckpointHeader =
+        serde_json::from_str(&line).map_err(|e| format!("checkpoint: {e}"))?;
+    let n = header.n;
+    if !(2..=MAX_N).contains(&n)
+        || header.cmp.iter().any(|&(a, b)| a >= b || b >= n)
+        || header.dsu.0.len() != n
+        || header.lens.len() != n
+        || header.used_cmp.len() != header.cmp.len()
+        || header.checked_cmp.len() != header.cmp.len()
+        || header.planner.dsu.0.len() != n
+        || header.planner.cmp_layered.len() != header.cmp.len()
+    {
+        return Err("checkpoint: inconsistent header".to_string());
+    }
+    Ok(header)
+}
+
+// Header of a checkpoint file, to choose the State before loading it
+pub fn load_checkpoint_header(path: &Path) -> Result<CheckpointHeader, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("checkpoint: {e}"))?;
+    read_checkpoint_header(&mut BufReader::new(file))
+}
+
+// Output positions of `target` that can get a wrong value: a 1 at p is wrong with fewer than
+// n - p ones in total, and a 0 at p is wrong with n - p ones or more.
+// Components are independent, so their fewest and most ones add up.
+fn wrong_positions<S: State>(n: usize, states: &[Vec<(S, S)>], target: S) -> S {
+    let comps = states.iter().filter(|v| !v.is_empty()).collect::<Vec<_>>();
+    let ones = |z: S, o: S| ((o & !z).count_ones(), o.count_ones());
+    let bounds = comps
+        .iter()
+        .map(|comp| {
+            comp.iter().fold((u32::MAX, 0), |(lo, hi), &(z, o)| {
+                let (l, h) = ones(z, o);
+                (lo.min(l), hi.max(h))
+            })
+        })
+        .collect::<Vec<_>>();
+    let (min_all, max_all) = bounds
+        .iter()
+        .fold((0, 0), |(lo, hi), &(l, h)| (lo + l, hi + h));
+    let mut wrong = S::ZERO;
+    for (comp, &(min_comp, max_comp)) in comps.iter().zip(bounds.iter()) {
+        let (z0, o0) = comp[0];
+        let mut rt = (z0 | o0) & target;
+        while rt != S::ZERO {
+            let p = rt.trailing_zeros() as usize;
+            rt &= rt - S::ONE;
+            let (mp, threshold) = (S::ONE << p, (n - p) as u32);
+            if comp.iter().any(|&(z, o)| {
+                let (l, h) = ones(z, o);
+                (o & mp != S::ZERO
+                    && l + (z & mp != S::ZERO) as u32 + min_all - min_comp < threshold)
+                    || (z & mp != S::ZERO
+                        && h - (o & mp != S::ZERO) as u32 + max_all - max_comp >= threshold)
+            }) {
+                wrong |= mp;
+            }
+        }
+    }
+    wrong
+}
+
+// The verification gets a thread of its own, so that a long one does not hold a worker of the pool
+fn execute_job_v2<S: State>(
+    _pool: Arc<threadpool::ThreadPool>,
+    progress_tx: mpsc::Sender<JobProgress<S>>,
+    cancel_state: Arc<AtomicBool>,
+    checkpoint_state: Arc<Mutex<Option<PathBuf>>>,
+    checkpoint: Checkpoint<S>,
+    timeout: Option<Duration>,
+) {
+    std::thread::spawn(move || {
+        let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            verify_worker(
+                &progress_tx,
+                &cancel_state,
+                &checkpoint_state,
+                checkpoint,
+                timeout,
+            )
+        }));
+        // A worker stopped by a dropped receiver has nobody to report to
+        if let Err(payload) = outcome {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            let log = format!("Verification failed, panic: {message}");
+            progress_tx.send(JobProgress::Error(log)).ok();
+        }
+    });
+}
+
+// Where the worker reports to: the channel of a JobResultFuture, or a callback on the current
+// thread. A send fails only when the receiver is gone, which ends the verification.
+trait ProgressSink<S: State> {
+    fn send(&self, progress: JobProgress<S>) -> Result<(), Box<mpsc::SendError<JobProgress<S>>>>;
+}
+impl<S: State> ProgressSink<S> for mpsc::Sender<JobProgress<S>> {
+    fn send(&self, progress: JobProgress<S>) -> Result<(), Box<mpsc::SendError<JobProgress<S>>>> {
+        mpsc::Sender::send(self, progress).map_err(Box::new)
+    }
+}
+struct Callback<F>(F);
+impl<S: State, F: Fn(JobProgress<S>)> ProgressSink<S> for Callback<F> {
+    fn send(&self, progress: JobProgress<S>) -> Result<(), Box<mpsc::SendError<JobProgress<S>>>> {
+        (self.0)(progress);
+        Ok(())
+    }
+}
+
+// The verification itself
+fn verify_worker<S: State>(
+    progress_tx: &impl ProgressSink<S>,
+    cancel_state: &Arc<AtomicBool>,
+    checkpoint_state: &Mutex<Option<PathBuf>>,
+    checkpoint: Checkpoint<S>,
+    timeout: Option<Duration>,
+) -> Result<(), Box<mpsc::SendError<JobProgress<S>>>> {
+    let begin_time = Instant::now();
+    // The timeout stops the job through the cancel flag; the watchdog wakes up early and
+    // leaves when the worker drops `_finished`. Without threads (wasm) the deadline is only
+    // checked between the jobs.
+    let (_finished, finished_rx) = mpsc::channel::<()>();
+    if let Some(timeout) = timeout {
+        let cancel_state = Arc::clone(cancel_state);
+        std::thread::Builder::new()
+            .spawn(move || {
+                if finished_rx.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                    cancel_state.store(true, Relaxed);
+                }
+            })
+            .ok();
+    }
+    let Checkpoint { header, mut states } = checkpoint;
+    let CheckpointHeader {
+        n,
+        cmp,
+        options,
+        mut planner,
+        job: first_job,
+        mut dsu,
+        mut used_cmp,
+        mut checked_cmp,
+        time: resumed_time,
+        ..
+    } = header;
+    let (cmp, options) = (Arc::new(cmp), Arc::new(options));
+    let strategy = options.strategy();
+    let mut result = JobResult::<S>::new(n, cmp.as_ref());
+    result.strategy = strategy.name();
+    result.progress_all = (cmp.len() as u64) + 1;
+    result.progress = checked_cmp.iter().filter(|&&c| c).count() as u64;
+    result.used = used_cmp.clone();
+    result.time = resumed_time;
+    progress_tx.send(JobProgress::Progress(result.clone()))?;
+    if options.count && n > COUNT_MAX_N {
+        let log = format!("Count is limited to n <= {COUNT_MAX_N}, n: {n}");
+        progress_tx.send(JobProgress::Error(log))?;
+        return Ok(());
+    }
+    // A stop after the deadline is the timeout, which reports the jobs completed so far.
+    // The receiver of a cancelled job may be gone already.
+    let stop = |result: &JobResult<S>| {
+        let elapsed = begin_time.elapsed();
+        if timeout.is_some_and(|timeout| elapsed >= timeout) {
+            let mut result = result.clone();
+            result.time = resumed_time + elapsed.as_millis() as u64;
+            result.log = format!(
+                "Timeout, progress: {progress}/{progress_all}, time: {time}ms",
+                progress = result.progress,
+                progress_all = result.progress_all,
+                time = result.time,
+            );
+            progress_tx.send(JobProgress::Timeout(result)).ok();
+        } else {
+            progress_tx.send(JobProgress::Cancel).ok();
+        }
+    };
+    // A pass over the memory budget stops with an error, keeping the progress made so far
+    let abort = |result: &mut JobResult<S>, log: String| {
+        result.log = log.clone();
+        result.time = resumed_time + begin_time.elapsed().as_millis() as u64;
+        progress_tx.send(JobProgress::Progress(result.clone()))?;
+        progress_tx.send(JobProgress::Error(log))
+    };
+    // The main loop and the follow-up passes check the deadline, without threads it is the only
+    // stop of the timeout
+    let stopped =
+        || cancel_state.load(Relaxed) || timeout.is_some_and(|t| begin_time.elapsed() >= t);
+    let wrong = if strategy.enumerates() {
+        // Every input at once: there are no jobs, so nothing to checkpoint
+        let begin_time_job = Instant::now();
+        let enumerated = match enumerate_inputs::<S>(n, &cmp, &options, cancel_state) {
+            Ok(enumerated) => enumerated,
+            Err(log) => return abort(&mut result, log),
+        };
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        result.used = enumerated.used;
+        result.unsorted = enumerated.unsorted;
+        result.wrong = enumerated.wrong;
+        result.failure_count = enumerated.failure_count;
+        result.progress = result.progress_all;
+        let log = format!(
+            "Enumerated, inputs: {inputs}, time: {elapsed_time}ms",
+            inputs = 1u64 << n,
+            elapsed_time = begin_time_job.elapsed().as_millis(),
+        );
+        result.log = log.clone();
+        result.time = resumed_time + begin_time.elapsed().as_millis() as u64;
+        progress_tx.send(JobProgress::Progress(result.clone()))?;
+        result.wrong.iter().fold(S::ZERO, |m, &p| m | (S::ONE << p))
+    } else {
+        for job_index in first_job.. {
+            if stopped() {
+                stop(&result);
+                return Ok(());
+            }
+            let checkpoint_path = checkpoint_state.lock().unwrap().take();
+            if let Some(path) = checkpoint_path {
+                let begin_time_job = Instant::now();
+                let checkpoint = Checkpoint {
+                    header: CheckpointHeader {
+                        n,
+                        bits: S::BITS,
+                        cmp: cmp.to_vec(),
+                        options: (*options).clone(),
+                        planner: planner.clone(),
+                        job: job_index,
+                        dsu: dsu.clone(),
+                        used_cmp: used_cmp.clone(),
+                        checked_cmp: checked_cmp.clone(),
+                        time: resumed_time + begin_time.elapsed().as_millis() as u64,
+                        lens: vec![],
+                    },
+                    states: std::mem::take(&mut states),
+                };
+                let log = match checkpoint.save(&path) {
+                    Ok(()) => format!(
+                        "Checkpoint, job: {job_index}, len: {len}, path: {path}, time: {elapsed_time}ms",
+                        len = checkpoint.states.iter().map(|v| v.len()).sum::<usize>(),
+                        path = path.display(),
+                        elapsed_time = begin_time_job.elapsed().as_millis(),
+                    ),
+                    Err(e) => format!("Checkpoint failed, {e}"),
+                };
+                states = checkpoint.states;
+                progress_tx.send(JobProgress::Log(log))?;
+            }
+            let Some(job) = planner.next_job(n, &cmp, &lens(&states), strategy) else {
+                break;
+            };
+            if let Err(log) =
+                check_budget(&options, Pass::Verify, job_index, &job, &states, |&zo| zo)
+            {
+                return abort(&mut result, log);
+            }
+            match job {
+                VerifyJob::Combine {
+                    root_master,
+                    root_slave,
+                } => {
+                    let begin_time_job = Instant::now();
+                    debug_assert_eq!(dsu.root(root_master), root_master);
+                    debug_assert_eq!(dsu.root(root_slave), root_slave);
+                    let (conn_nodes_master, conn_nodes_slave) =
+                        (dsu.size(root_master), dsu.size(root_slave));
+                    let unite_result = dsu.unite(root_master, root_slave);
+                    debug_assert!(unite_result);
+                    debug_assert_eq!(dsu.root(root_master), root_master);
+                    let conn_nodes_united = dsu.size(root_master);
+                    let master_len = states[root_master].len();
+                    let slave_len = states[root_slave].len();
+                    let mut united_status = vec![(S::ZERO, S::ZERO); master_len * slave_len];
+                    let (master, slave) = (&states[root_master], &states[root_slave]);
+                    // Fixed-size chunks rather than one per slave state, to check for cancellation
+                    united_status.par_chunks_mut(65536).enumerate().for_each(
+                        |(c, united_status_chunk)| {
+                            if cancel_state.load(Relaxed) {
+                                return;
+                            }
+                            let begin = c * 65536;
+                            let (mut s, mut m) = (begin / master_len, begin % master_len);
+                            for united_status in united_status_chunk.iter_mut() {
+                                let ((sz, so), (mz, mo)) = (slave[s], master[m]);
+                                *united_status = (sz | mz, so | mo);
+                                m += 1;
+                                if m == master_len {
+                                    (s, m) = (s + 1, 0);
+                                }
+                            }
+                        },
+                    );
+                    if cancel_state.load(Relaxed) {
+                        stop(&result);
+                        return Ok(());
+                    }
+                    let united_len = united_status.len();
+                    states[root_slave] = vec![];
+                    states[root_master] = united_status;
+                    let elapsed_time = begin_time_job.elapsed().as_millis() as u64;
+                    let log = format!(
+                        "Combining, conn: {conn_nodes_master}+{conn_nodes_slave}=>{conn_nodes_united}, root: ({root_master},{root_slave}), len: {master_len}*{slave_len}=>{united_len}, time: {elapsed_time}ms"
+                    );
+                    progress_tx.send(JobProgress::Log(log))?;
+                }
+                VerifyJob::Cmp { root, cmp_part } => {
+                    let begin_time_job = Instant::now();
+                    let mut elapsed_times = vec![];
+                    debug_assert_eq!(dsu.root(root), root);
+                    debug_assert!(
+                        cmp_part
+                            .iter()
+                            .all(|&CeEntry { cei: _, a, b }| dsu.equiv(root, a)
+                                && dsu.equiv(root, b))
+                    );
+                    let conn_nodes = dsu.size(root);
+                    let pre_len = states[root].len();
+                    let states_root = &mut states[root];
+                    let (par_unused_cmp, par_extend_states): (Vec<_>, Vec<_>) = states_root
+                        .par_chunks_mut(65536)
+                        .map(|states_chunk| {
+                            let mut stack =
+                                Vec::<(usize, S, S)>::with_capacity(states_chunk.len() + n);
+                            let mut extend_states = Vec::new();
+                            let mut used_cmp_local = vec![false; cmp.len()];
+                            for st in states_chunk.iter_mut() {
+                                if cancel_state.load(Relaxed) {
+                                    return (used_cmp_local, extend_states);
+                                }
+                                let (mut z, mut o) = *st;
+                                for (i, &CeEntry { cei, a, b }) in cmp_part.iter().enumerate() {
+                                    if S::ONE & (o >> a) & (z >> b) == S::ZERO {
+                                        continue;
+                                    } else if S::ONE & (z >> a) & (o >> b) == S::ZERO {
+                                        used_cmp_local[cei] = true;
+                                        let (xz, xo) = (
+                                            ((z >> a) ^ (z >> b)) & S::ONE,
+                                            ((o >> a) ^ (o >> b)) & S::ONE,
+                                        );
+                                        z ^= xz << a | xz << b;
+                                        o ^= xo << a | xo << b;
+                                    } else {
+                                        used_cmp_local[cei] = true;
+                                        stack.push((i + 1, z, o ^ (S::ONE << a) ^ (S::ONE << b)));
+                                        z ^= S::ONE << b;
+                                    }
+                                }
+                                *st = (z, o);
+                            }
+                            while let Some((mut i, mut z, mut o)) = stack.pop() {
+                                if cancel_state.load(Relaxed) {
+                                    return (used_cmp_local, extend_states);
+                                }
+                                while let Some(&CeEntry { cei, a, b }) = cmp_part.get(i) {
+                                    i += 1;
+                                    if (o >> a) & S::ONE == S::ZERO || (z >> b) & S::ONE == S::ZERO
+                                    {
+                                        continue;
+                                    } else if (z >> a) & S::ONE == S::ZERO
+                                        || (o >> b) & S::ONE == S::ZERO
+                                    {
+                                        used_cmp_local[cei] = true;
+                                        let (xz, xo) = (
+                                            ((z >> a) ^ (z >> b)) & S::ONE,
+                                            ((o >> a) ^ (o >> b)) & S::ONE,
+                                        );
+                                        z ^= xz << a | xz << b;
+                                        o ^= xo << a | xo << b;
+                                    } else {
+                                        used_cmp_local[cei] = true;
+                                        stack.push((i, z, o ^ (S::ONE << a) ^ (S::ONE << b)));
+                                        z ^= S::ONE << b;
+                                    }
+                                }
+                                extend_states.push((z, o));
+                            }
+                            extend_states.sort_unstable();
+                            extend_states.dedup();
+                            (used_cmp_local, extend_states)
+                        })
+                        .unzip();
+                    if cancel_state.load(Relaxed) {
+                        stop(&result);
+                        return Ok(());
+                    }
+                    elapsed_times.push(("states", begin_time_job.elapsed().as_millis()));
+                    for unused_part in par_unused_cmp.iter() {
+                        for (uroot, &ue) in used_cmp.iter_mut().zip(unused_part.iter()) {
+                            *uroot |= ue;
+                        }
+                    }
+                    elapsed_times.push(("unused", begin_time_job.elapsed().as_millis()));
+                    let ext_len = par_extend_states.iter().map(|v| v.len()).sum();
+                    states_root.reserve(ext_len);
+                    for extend_states in par_extend_states {
+                        states_root.extend(extend_states);
+                    }
+                    if cancel_state.load(Relaxed) {
+                        stop(&result);
+                        return Ok(());
+                    }
+                    elapsed_times.push(("extend", begin_time_job.elapsed().as_millis()));
+                    let gen_len = states_root.len();
+                    // dedupulicate
+                    if ext_len > 0 {
+                        par_sort_cancellable(states_root, cancel_state, |a, b| a < b);
+                        if cancel_state.load(Relaxed) {
+                            stop(&result);
+                            return Ok(());
+                        }
+                        elapsed_times.push(("sort", begin_time_job.elapsed().as_millis()));
+                        states_root.dedup();
+                        if cancel_state.load(Relaxed) {
+                            stop(&result);
+                            return Ok(());
+                        }
+                        elapsed_times.push(("dedup", begin_time_job.elapsed().as_millis()));
+                    }
+                    let dedup_len = states_root.len();
+                    // send result
+                    result.used = used_cmp.clone();
+                    for &CeEntry { cei, a: _, b: _ } in cmp_part.iter() {
+                        result.progress += 1;
+                        checked_cmp[cei] = true;
+                    }
+                    if cancel_state.load(Relaxed) {
+                        stop(&result);
+                        return Ok(());
+                    }
+                    let log = format!(
+                        "AppliedCE, conn: {conn_nodes}, root: {root}, len: {pre_len}=>{gen_len}=>{dedup_len}, cmp: {cmp_part:?}, time: {elapsed_times:?}ms"
+                    );
+                    result.log = log.clone();
+                    result.time = resumed_time + begin_time.elapsed().as_millis() as u64;
+                    progress_tx.send(JobProgress::Progress(result.clone()))?;
+                    elapsed_times.push(("send", begin_time_job.elapsed().as_millis()));
+                }
+            }
+        }
+        fn check_unsorted<S: State>(unsorted: &mut [S], z: S, o: S) {
+            let (rz, mut ro) = (z, o);
+            while ro != S::ZERO {
+                let i = ro.trailing_zeros() as usize;
+                unsorted[i] |= rz & ((S::MAX << 1) << i);
+                ro &= ro - S::ONE;
+            }
+        }
+        // No input satisfies the preconditions if a component is left without states
+        let satisfiable = (0..n).all(|u| dsu.root(u) != u || !states[u].is_empty());
+        let ones_all = states.iter().flatten().fold(S::ZERO, |m, &(_, o)| m | o);
+        for states_par_root in states.iter().filter(|_| satisfiable) {
+            let unsorted = &mut result.unsorted;
+            let (q_mask, zeros) = states_par_root
+                .iter()
+                .fold((S::ZERO, S::ZERO), |(q, zs), &(z, o)| (q | z | o, zs | z));
+            // Components are independent: any 1 of another component meets any 0 of this one
+            check_unsorted(unsorted, zeros, ones_all & !q_mask);
+            for &(z, o) in states_par_root.iter() {
+                check_unsorted(unsorted, z, o);
+            }
+        }
+        // Keep only the unsorted pairs touching a target position that can be wrong
+        let mut wrong = S::ZERO;
+        if satisfiable && !options.target.is_empty() {
+            let target = options
+                .target
+                .iter()
+                .fold(S::ZERO, |m, &p| m | (S::ONE << p));
+            wrong = wrong_positions(n, &states, target);
+            result.wrong = (0..n)
+                .filter(|&p| (wrong >> p) & S::ONE != S::ZERO)
+                .collect();
+            for (i, unsorted) in result.unsorted.iter_mut().enumerate() {
+                if (wrong >> i) & S::ONE == S::ZERO {
+                    *unsorted &= wrong;
+                }
+            }
+        }
+        wrong
+    };
+    let unsorted_pairs = result.get_unsorted_allpairs();
+    // The witnesses and the count verify the network again, each pass is logged as it starts
+    if !unsorted_pairs.is_empty() {
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        let begin_time_job = Instant::now();
+        let log = format!("Counterexample started, pairs: {}", unsorted_pairs.len());
+        progress_tx.send(JobProgress::Log(log))?;
+        // One pass of witness states serves both the pairs and the target position
+        let mut witness = match witness_states::<S>(n, &cmp, &options, cancel_state) {
+            Ok(witness) => witness,
+            Err(log) => return abort(&mut result, log),
+        };
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        let witnesses =
+            find_unsorted_witnesses::<S>(n, &cmp, &mut witness, &unsorted_pairs, cancel_state);
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        result.witnesses = witnesses;
+        debug_assert_eq!(result.witnesses.len(), unsorted_pairs.len());
+        result.counterexample = result
+            .witnesses
+            .iter()
+            .min_by_key(|(_, c)| c.input.iter().filter(|&&x| x != 0).count())
+            .map(|(_, c)| c.clone());
+        if wrong != S::ZERO {
+            let p = wrong.trailing_zeros() as usize;
+            let log = format!("Counterexample, target: {p}");
+            progress_tx.send(JobProgress::Log(log))?;
+            let input = find_target_input::<S>(n, &witness.1, p);
+            result.counterexample =
+                input.map(|input| Counterexample::new(n, input, apply_network(&cmp, input)));
+        }
+        let log = format!(
+            "Counterexample, pairs: {found}/{pairs}, time: {elapsed_time}ms",
+            found = result.witnesses.len(),
+            pairs = unsorted_pairs.len(),
+            elapsed_time = begin_time_job.elapsed().as_millis(),
+        );
+        progress_tx.send(JobProgress::Log(log))?;
+    }
+    // The enumeration counted the failing inputs already
+    if options.count && result.failure_count.is_none() {
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        let begin_time_job = Instant::now();
+        progress_tx.send(JobProgress::Log("Count started".to_string()))?;
+        let failure_count = match count_failing_inputs::<S>(n, &cmp, &options, cancel_state) {
+            Ok(failure_count) => failure_count,
+            Err(log) => return abort(&mut result, log),
+        };
+        if stopped() {
+            stop(&result);
+            return Ok(());
+        }
+        let log = format!(
+            "Count, failing: {failing}/{inputs}, time: {elapsed_time}ms",
+            failing = failure_count.failing,
+            inputs = failure_count.inputs,
+            elapsed_time = begin_time_job.elapsed().as_millis(),
+        );
+        progress_tx.send(JobProgress::Log(log))?;
+        result.failure_count = Some(failure_count);
+    }
+    result.progress = result.progress_all;
+    result.time = resumed_time + begin_time.elapsed().as_millis() as u64;
+    let log = format!(
+        "Finished, strategy: {strategy}, progress: {progress}/{progress_all}, unused_cmp: {cmp_unused}/{cmp_count}, unsorted: {unsorted}/{unsorted_all} ({unsorted_d}/{unsorted_d_all}), time: {time}ms",
+        strategy = result.strategy,
+        progress = result.progress,
+        progress_all = result.progress_all,
+        cmp_unused = result.get_unused().iter().filter(|&&u| u).count(),
+        cmp_count = cmp.len(),
+        unsorted = result.unsorted.iter().map(|x| x.count_ones()).sum::<u32>(),
+        unsorted_all = (n * (n - 1) / 2),
+        unsorted_d = result
+            .unsorted
+            .iter()
+            .enumerate()
+            .filter(|&(i, &x)| ((x >> i) & (S::ONE << 1)) != S::ZERO)
+            .count(),
+        unsorted_d_all = n - 1,
+        time = result.time,
+    );
+    result.log = log.clone();
+    progress_tx.send(JobProgress::Progress(result.clone()))?;
+    progress_tx.send(JobProgress::Done)?;
+    Ok(())
+}
+
+// Apply the network to a 0-1 input vector
+pub fn apply_network<S: State>(cmp: &[(usize, usize)], mut x: S) -> S {
+    for &(a, b) in cmp.iter() {
+        if (x >> a) & !(x >> b) & S::ONE != S::ZERO {
+            x ^= (S::ONE << a) ^ (S::ONE << b);
+        }
+    }
+    x
+}
+
+// The output states rebuilt with the same strategy, but a free value is never moved to another
+// position (it is branched on instead), so that each state (z, o, x) also knows its input:
+// x holds the input bits fixed so far, and a free position is still driven by its own input.
+fn witness_states<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+    cancel_state: &AtomicBool,
+) -> Result<Components<(S, S, S)>, String> {
+    let (mut dsu, states) = initial_states::<S>(n, options);
+    let mut planner = Planner::new(cmp, dsu.clone());
+    let mut states = states
+        .into_iter()
+        .map(|v| {
+            v.into_iter()
+                .map(|(z, o)| (z, o, o & !z))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for job_index in 0.. {
+        let Some(job) = planner.next_job(n, cmp, &lens(&states), options.strategy()) else {
+            break;
+        };
+        if cancel_state.load(Relaxed) {
+            break;
+        }
+        check_budget(
+            options,
+            Pass::Witness,
+            job_index,
+            &job,
+            &states,
+            |&(z, o, _)| (z, o),
+        )?;
+        match job {
+            VerifyJob::Combine {
+                root_master,
+                root_slave,
+            } => {
+                dsu.unite(root_master, root_slave);
+                let slave = std::mem::take(&mut states[root_slave]);
+                states[root_master] = slave
+                    .par_iter()
+                    .filter(|_| !cancel_state.load(Relaxed))
+                    .flat_map_iter(|&(sz, so, sx)| {
+                        states[root_master]
+                            .iter()
+                            .map(move |&(mz, mo, mx)| (sz | mz, so | mo, sx | mx))
+                    })
+                    .collect();
+            }
+            VerifyJob::Cmp { root, cmp_part } => {
+                let mut next = states[root]
+                    .par_chunks(65536)
+                    .flat_map_iter(|states_chunk| {
+                        let mut stack = states_chunk
+                            .iter()
+                            .map(|&(z, o, x)| (0, z, o, x))
+                            .collect::<Vec<_>>();
+                        let mut next = Vec::with_capacity(states_chunk.len());
+                        while let Some((mut i, mut z, mut o, mut x)) = stack.pop() {
+                            if cancel_state.load(Relaxed) {
+                                break;
+                            }
+                            while let Some(&CeEntry { cei: _, a, b }) = cmp_part.get(i) {
+                                i += 1;
+                                let (ma, mb) = (S::ONE << a, S::ONE << b);
+                                if o & ma == S::ZERO || z & mb == S::ZERO {
+                                    continue;
+                                }
+                                match (z & ma != S::ZERO, o & mb != S::ZERO) {
+                                    // (1, 0) => (0, 1)
+                                    (false, false) => {
+                                        z ^= ma | mb;
+                                        o ^= ma | mb;
+                                    }
+                                    // (x, y) => (x, 1) if y = 1, (0, 0) if x = y = 0
+                                    (true, true) => {
+                                        stack.push((i, z, o & !ma & !mb, x));
+                                        z &= !mb;
+                                        x |= mb;
+                                    }
+                                    // (1, y) => (0, 1) if y = 0, (1, 1) if y = 1
+                                    (false, true) => {
+                                        stack.push((i, (z | ma) & !mb, o & !ma, x));
+                                        z &= !mb;
+                                        x |= mb;
+                                    }
+                                    // (x, 0) => (0, 0) if x = 0, (0, 1) if x = 1
+                                    (true, false) => {
+                                        stack.push((i, z, o & !ma, x));
+                                        z &= !mb;
+                                        o = (o & !ma) | mb;
+                                        x |= ma;
+                                    }
+                                }
+                            }
+                            next.push((z, o, x));
+                        }
+                        next
+                    })
+                    .collect::<Vec<_>>();
+                // Inputs of equal (z, o) have the same weight, keep any of them
+                par_sort_cancellable(&mut next, cancel_state, |a, b| a < b);
+                next.dedup_by_key(|&mut (z, o, _)| (z, o));
+                states[root] = next;
+            }
+        }
+    }
+    Ok((dsu, states))
+}
+
+// Find 0-1 input vectors that leave a 1 at i and a 0 at j (i < j) for each of `pairs`, from the
+// witness states. The states of each component are left sorted by the weight of their input.
+fn find_unsorted_inputs<S: State>(
+    n: usize,
+    (dsu, states): &mut Components<(S, S, S)>,
+    pairs: &[(usize, usize)],
+    cancel_state: &AtomicBool,
+) -> Vec<Option<S>> {
+    // Comparators keep the number of ones, so the lightest state gives the lightest input.
+    // A pair (i, j) costs |x| if i is fixed to 1, and |x| + 1 if i is left free,
+    // and every other component adds its own lightest input.
+    let mut index = vec![usize::MAX; n * n];
+    let mut pending = vec![S::ZERO; n];
+    for (k, &(i, j)) in pairs.iter().enumerate() {
+        debug_assert!(i < j && j < n);
+        index[i * n + j] = k;
+        pending[i] |= S::ONE << j;
+    }
+    let mut best = vec![None::<(u32, S)>; pairs.len()];
+    let roots = (0..n).map(|u| dsu.root(u)).collect::<Vec<_>>();
+    let mut comp_masks = vec![S::ZERO; n];
+    let mut lightest = vec![None::<S>; n];
+    // Lightest inputs leaving a 1 or a 0 at each position, for pairs across components
+    let mut one_at = vec![None::<(u32, S)>; n];
+    let mut zero_at = vec![None::<S>; n];
+    for root in (0..n).filter(|&u| roots[u] == u) {
+        let comp_mask = (0..n)
+            .filter(|&u| roots[u] == root)
+            .fold(S::ZERO, |m, u| m | (S::ONE << u));
+        comp_masks[root] = comp_mask;
+        let mut comp_states = std::mem::take(&mut states[root]);
+        par_sort_cancellable(&mut comp_states, cancel_state, |a, b| {
+            a.2.count_ones() < b.2.count_ones()
+        });
+        lightest[root] = comp_states.first().map(|&(_, _, x)| x);
+        let mut zero_open = comp_mask;
+        let mut tentative = vec![S::ZERO; n];
+        let mut weight = 0;
+        for &(z, o, x) in comp_states.iter() {
+            if cancel_state.load(Relaxed) {
+                break;
+            }
+            let w = x.count_ones();
+            if w != weight {
+                // Costs up to the previous weight can no longer be improved
+                for (p, t) in pending.iter_mut().zip(tentative.iter_mut()) {
+                    *p &= !*t;
+                    *t = S::ZERO;
+                }
+                weight = w;
+            }
+            let mut rz = z & zero_open;
+            zero_open &= !rz;
+            while rz != S::ZERO {
+                let j = rz.trailing_zeros() as usize;
+                rz &= rz - S::ONE;
+                zero_at[j] = Some(x);
+            }
+            let mut ro = o & comp_mask;
+            while ro != S::ZERO {
+                let i = ro.trailing_zeros() as usize;
+                ro &= ro - S::ONE;
+                let free_i = z & (S::ONE << i);
+                let cost = w + (free_i != S::ZERO) as u32;
+                if one_at[i].is_none_or(|(c, _)| cost < c) {
+                    one_at[i] = Some((cost, x | free_i));
+                }
+                let mut hits = pending[i] & z & comp_mask & ((S::MAX << 1) << i);
+                if hits == S::ZERO {
+                    continue;
+                }
+                if free_i == S::ZERO {
+                    pending[i] &= !hits;
+                } else {
+                    tentative[i] |= hits;
+                }
+                while hits != S::ZERO {
+                    let j = hits.trailing_zeros() as usize;
+                    hits &= hits - S::ONE;
+                    let b = &mut best[index[i * n + j]];
+                    if b.is_none_or(|(c, _)| cost < c) {
+                        *b = Some((cost, x | free_i));
+                    }
+                }
+            }
+        }
+        states[root] = comp_states;
+    }
+    // The other components take their lightest inputs
+    let mut rest = S::ZERO;
+    for root in (0..n).filter(|&u| roots[u] == u) {
+        match lightest[root] {
+            Some(x) => rest |= x,
+            None => return vec![None; pairs.len()],
+        }
+    }
+    pairs
+        .iter()
+        .zip(best)
+        .map(|(&(i, j), b)| {
+            let (ci, cj) = (comp_masks[roots[i]], comp_masks[roots[j]]);
+            if roots[i] == roots[j] {
+                b.map(|(_, x)| x | (rest & !ci))
+            } else {
+                one_at[i]
+                    .zip(zero_at[j])
+                    .map(|((_, xi), xj)| xi | xj | (rest & !ci & !cj))
+            }
+        })
+        .collect()
+}
+
+// Minimal 0-1 inputs (fewest ones) that leave a 1 at i above a 0 at j, for each pair (i, j)
+fn find_unsorted_witnesses<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    witness: &mut Components<(S, S, S)>,
+    pairs: &[(usize, usize)],
+    cancel_state: &AtomicBool,
+) -> Vec<Witness> {
+    let inputs = find_unsorted_inputs::<S>(n, witness, pairs, cancel_state);
+    pairs
+        .iter()
+        .zip(inputs)
+        .filter_map(|(&pair, input)| {
+            input.map(|input| {
+                (
+                    pair,
+                    Counterexample::new(n, input, apply_network(cmp, input)),
+                )
+            })
+        })
+        .collect()
+}
+
+// A 0-1 input that leaves a wrong value at output position p (see wrong_positions), from the
+// witness states
+fn find_target_input<S: State>(n: usize, states: &[Vec<(S, S, S)>], p: usize) -> Option<S> {
+    let comps = states.iter().filter(|v| !v.is_empty()).collect::<Vec<_>>();
+    // Lightest and heaviest input of each component
+    let extremes = comps
+        .iter()
+        .map(|comp| {
+            let light = comp
+                .iter()
+                .map(|&(_, _, x)| x)
+                .min_by_key(|x| x.count_ones());
+            let heavy = comp
+                .iter()
+                .map(|&(z, o, x)| x | (z & o))
+                .max_by_key(|x| x.count_ones());
+            (light.unwrap_or(S::ZERO), heavy.unwrap_or(S::ZERO))
+        })
+        .collect::<Vec<_>>();
+    let (mp, threshold) = (S::ONE << p, (n - p) as u32);
+    for (c, comp) in comps.iter().enumerate() {
+        let (z0, o0, _) = comp[0];
+        if (z0 | o0) & mp == S::ZERO {
+            continue;
+        }
+        let (light_rest, heavy_rest) = extremes
+            .iter()
+            .enumerate()
+            .filter(|&(d, _)| d != c)
+            .fold((S::ZERO, S::ZERO), |(l, h), (_, &(dl, dh))| {
+                (l | dl, h | dh)
+            });
+        for &(z, o, x) in comp.iter() {
+            if o & mp != S::ZERO {
+                let input = x | (z & mp) | light_rest;
+                if input.count_ones() < threshold {
+                    return Some(input);
+                }
+            }
+            if z & mp != S::ZERO {
+                let input = x | (z & o & !mp) | heavy_rest;
+                if input.count_ones() >= threshold {
+                    return Some(input);
+                }
+            }
+        }
+    }
+    None
+}
+
+// The output states rebuilt with the same strategy, where each state (z, o, c) is hit by exactly
+// c inputs per point. Both inputs of a comparator free is split three ways so that every piece
+// maps one-to-one: (x, 1) => (x, 1), (0, 0) => (0, 0) and (1, 0) => (0, 1).
+fn count_states<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+    cancel_state: &AtomicBool,
+) -> Result<Components<(S, S, u128)>, String> {
+    let (mut dsu, states) = initial_states::<S>(n, options);
+    let mut planner = Planner::new(cmp, dsu.clone());
+    let mut states = states
+        .into_iter()
+        .map(|v| v.into_iter().map(|(z, o)| (z, o, 1)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    for job_index in 0.. {
+        let Some(job) = planner.next_job(n, cmp, &lens(&states), options.strategy()) else {
+            break;
+        };
+        if cancel_state.load(Relaxed) {
+            break;
+        }
+        check_budget(
+            options,
+            Pass::Count,
+            job_index,
+            &job,
+            &states,
+            |&(z, o, _)| (z, o),
+        )?;
+        match job {
+            VerifyJob::Combine {
+                root_master,
+                root_slave,
+            } => {
+                dsu.unite(root_master, root_slave);
+                let slave = std::mem::take(&mut states[root_slave]);
+                states[root_master] = slave
+                    .par_iter()
+                    .filter(|_| !cancel_state.load(Relaxed))
+                    .flat_map_iter(|&(sz, so, sc)| {
+                        states[root_master]
+                            .iter()
+                            .map(move |&(mz, mo, mc)| (sz | mz, so | mo, sc * mc))
+                    })
+                    .collect();
+            }
+            VerifyJob::Cmp { root, cmp_part } => {
+                let mut next = states[root]
+                    .par_chunks(65536)
+                    .flat_map_iter(|states_chunk| {
+                        let mut stack = states_chunk
+                            .iter()
+                            .map(|&(z, o, c)| (0, z, o, c))
+                            .collect::<Vec<_>>();
+                        let mut next = Vec::with_capacity(states_chunk.len());
+                        while let Some((mut i, mut z, mut o, c)) = stack.pop() {
+                            if cancel_state.load(Relaxed) {
+                                break;
+                            }
+                            while let Some(&CeEntry { cei: _, a, b }) = cmp_part.get(i) {
+                                i += 1;
+                                let (ma, mb) = (S::ONE << a, S::ONE << b);
+                                if o & ma == S::ZERO || z & mb == S::ZERO {
+                                    continue;
+                                } else if z & ma == S::ZERO || o & mb == S::ZERO {
+                                    // Always swapped when different, a free value moves along
+                                    let (xz, xo) = (
+                                        ((z >> a) ^ (z >> b)) & S::ONE,
+                                        ((o >> a) ^ (o >> b)) & S::ONE,
+                                    );
+                                    z ^= xz << a | xz << b;
+                                    o ^= xo << a | xo << b;
+                                } else {
+                                    stack.push((i, z, o & !ma & !mb, c));
+                                    stack.push((i, z & !mb, o & !ma, c));
+                                    z &= !mb;
+                                }
+                            }
+                            next.push((z, o, c));
+                        }
+                        next
+                    })
+                    .collect::<Vec<_>>();
+                par_sort_cancellable(&mut next, cancel_state, |a, b| (a.0, a.1) < (b.0, b.1));
+                let mut merged = Vec::<(S, S, u128)>::with_capacity(next.len());
+                for (z, o, c) in next {
+                    match merged.last_mut() {
+                        Some(last) if (last.0, last.1) == (z, o) => last.2 += c,
+                        _ => merged.push((z, o, c)),
+                    }
+                }
+                states[root] = merged;
+            }
+        }
+    }
+    Ok((dsu, states))
+}
+
+// Inputs by their number of ones, restricted to the points with `ones` set and `zeros` cleared:
+// the sum of c x^fixed_ones (1 + x)^free over the states
+fn ones_poly<S: State>(
+    states: &[(S, S, u128)],
+    binom: &[Vec<u128>],
+    ones: S,
+    zeros: S,
+) -> Vec<u128> {
+    let mut groups = HashMap::<(u32, u32), u128>::new();
+    for &(z, o, c) in states.iter() {
+        if ones & !o != S::ZERO || zeros & !z != S::ZERO {
+            continue;
+        }
+        let (z, o) = (z & !ones, o & !zeros);
+        *groups
+            .entry(((o & !z).count_ones(), (z & o).count_ones()))
+            .or_default() += c;
+    }
+    let mut poly = vec![0; binom.len()];
+    for ((f, g), c) in groups {
+        for (j, &b) in binom[g as usize].iter().enumerate() {
+            poly[f as usize + j] += c * b;
+        }
+    }
+    poly
+}
+
+fn poly_mul(a: &[u128], b: &[u128]) -> Vec<u128> {
+    let mut poly = vec![0; a.len()];
+    for (i, &x) in a.iter().enumerate().filter(|&(_, &x)| x != 0) {
+        for (j, &y) in b.iter().enumerate().take(a.len() - i) {
+            poly[i + j] += x * y;
+        }
+    }
+    poly
+}
+
+// Largest n whose 2^n inputs fit in the u128 counts
+pub const COUNT_MAX_N: usize = 127;
+
+// Count the 0-1 inputs leaving a wrong value at the target positions (all positions by default)
+// and at each output position. An output is right at p iff it has a 1 at p exactly when it has
+// at least n - p ones, and the components are independent, so their polynomials multiply.
+pub fn count_failing_inputs<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+    cancel_state: &AtomicBool,
+) -> Result<FailureCount, String> {
+    assert!(n <= COUNT_MAX_N);
+    let (mut dsu, mut states) = count_states::<S>(n, cmp, options, cancel_state)?;
+    let mut binom = vec![vec![1u128]];
+    for g in 1..=n {
+        let prev = &binom[g - 1];
+        let row = (0..=g)
+            .map(|j| {
+                if j == 0 || j == g {
+                    1
+                } else {
+                    prev[j - 1] + prev[j]
+                }
+            })
+            .collect();
+        binom.push(row);
+    }
+    let roots = (0..n).filter(|&u| dsu.root(u) == u).collect::<Vec<_>>();
+    let masks = roots
+        .iter()
+        .map(|&root| {
+            (0..n)
+                .filter(|&u| dsu.equiv(u, root))
+                .fold(S::ZERO, |m, u| m | (S::ONE << u))
+        })
+        .collect::<Vec<_>>();
+    let comps = roots
+        .iter()
+        .map(|&root| std::mem::take(&mut states[root]))
+        .collect::<Vec<_>>();
+    let mut unit = vec![0u128; n + 1];
+    unit[0] = 1;
+    let polys = comps
+        .iter()
+        .map(|comp| ones_poly(comp, &binom, S::ZERO, S::ZERO))
+        .collect::<Vec<_>>();
+    // Products of the components before / from each index
+    let mut prefix = vec![unit.clone()];
+    for poly in polys.iter() {
+        prefix.push(poly_mul(prefix.last().unwrap(), poly));
+    }
+    let mut suffix = vec![unit.clone()];
+    for poly in polys.iter().rev() {
+        suffix.push(poly_mul(poly, suffix.last().unwrap()));
+    }
+    suffix.reverse();
+    let inputs = prefix[comps.len()].iter().sum::<u128>();
+    // above[t]: positions t and higher, which hold the ones of a sorted output with n - t ones
+    let mut above = vec![S::ZERO; n + 1];
+    for t in (0..n).rev() {
+        above[t] = above[t + 1] | (S::ONE << t);
+    }
+    let target = if options.target.is_empty() {
+        above[0]
+    } else {
+        options
+            .target
+            .iter()
+            .fold(S::ZERO, |m, &p| m | (S::ONE << p))
+    };
+    // Each ones_poly is a pass over the states, a cancelled count stops between two of them
+    let correct = (0..=n)
+        .take_while(|_| !cancel_state.load(Relaxed))
+        .map(|k| {
+            let (ones, zeros) = (target & above[n - k], target & !above[n - k]);
+            comps
+                .iter()
+                .zip(masks.iter())
+                .fold(unit.clone(), |p, (comp, &m)| {
+                    poly_mul(&p, &ones_poly(comp, &binom, ones & m, zeros & m))
+                })[k]
+        })
+        .sum::<u128>();
+    let mut failing_at = vec![0; n];
+    for (ci, (comp, &m)) in comps.iter().zip(masks.iter()).enumerate() {
+        let rest = poly_mul(&prefix[ci], &suffix[ci + 1]);
+        for (p, failing_at) in failing_at.iter_mut().enumerate() {
+            let mp = S::ONE << p;
+            if m & mp == S::ZERO || cancel_state.load(Relaxed) {
+                continue;
+            }
+            let one = poly_mul(&ones_poly(comp, &binom, mp, S::ZERO), &rest);
+            let zero = poly_mul(&ones_poly(comp, &binom, S::ZERO, mp), &rest);
+            *failing_at = one[..n - p].iter().sum::<u128>() + zero[n - p..].iter().sum::<u128>();
+        }
+    }
+    Ok(FailureCount {
+        inputs,
+        failing: inputs - correct,
+        failing_at,
+    })
+}
+
+// Estimated cost of one job of the verification plan
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlanStep {
+    pub job: String,
+    // States of the root after the job
+    pub len: f64,
+    // States generated before deduplicating
+    pub generated: f64,
+    // Peak bytes of all states during the job
+    pub memory: f64,
+    // Milliseconds
+    pub time: f64,
+    // Whether the counts are exact, not scaled from a sample
+    pub exact: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VerifyPlan {
+    pub strategy: String,
+    pub steps: Vec<PlanStep>,
+    pub memory: f64,
+    pub time: f64,
+}
+
+// Largest number of states per root the estimator keeps
+const PLAN_SAMPLE: usize = 1 << 16;
+
+// Apply a Cmp job to the states without deduplicating them
+fn expand_states<S: State>(states: &[(S, S)], cmp_part: &[CeEntry]) -> Vec<(S, S)> {
+    let free = |z: S, o: S, c: usize| (z >> c) & (o >> c) & S::ONE != S::ZERO;
+    let mut expanded = states.to_vec();
+    for &CeEntry { a, b, .. } in cmp_part.iter() {
+        let mut next = Vec::with_capacity(expanded.len());
+        for &(z, o) in expanded.iter() {
+            if (o >> a) & (z >> b) & S::ONE == S::ZERO {
+                next.push((z, o));
+            } else if free(z, o, a) && free(z, o, b) {
+                next.push((z & !(S::ONE << b), o));
+                next.push((z, o & !(S::ONE << a) & !(S::ONE << b)));
+            } else {
+                let (xz, xo) = (
+                    ((z >> a) ^ (z >> b)) & S::ONE,
+                    ((o >> a) ^ (o >> b)) & S::ONE,
+                );
+                next.push((z ^ (xz << a | xz << b), o ^ (xo << a | xo << b)));
+            }
+        }
+        expanded = next;
+    }
+    expanded
+}
+
+// One step for the enumerate strategy, timed on the first PLAN_SAMPLE inputs. It keeps no states,
+// only a tally of a few bytes per channel and comparator.
+fn estimate_enumeration<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+) -> VerifyPlan {
+    let inputs = 2f64.powi(n as i32);
+    let bits = n.min(PLAN_SAMPLE.trailing_zeros() as usize);
+    let begin_time = Instant::now();
+    let outputs = (0..1usize << bits)
+        .into_par_iter()
+        .map(|x| {
+            let input = (0..bits)
+                .filter(|&i| (x >> i) & 1 == 1)
+                .fold(S::ZERO, |m, i| m | (S::ONE << i));
+            apply_network(cmp, input)
+        })
+        .collect::<Vec<_>>();
+    let time = begin_time.elapsed().as_nanos() as f64 / 1e6 * inputs / outputs.len() as f64;
+    let step = PlanStep {
+        job: format!("Enumerate, inputs: {inputs}"),
+        len: inputs,
+        generated: inputs,
+        memory: ((n + 1) * std::mem::size_of::<u128>() + cmp.len()) as f64,
+        time,
+        exact: true,
+    };
+    VerifyPlan {
+        strategy: options.strategy().name().to_string(),
+        memory: step.memory,
+        time: step.time,
+        steps: vec![step],
+    }
+}
+
+// Estimate the states, memory and time of each job of the plan, without running it.
+// The states are followed exactly while each root has at most PLAN_SAMPLE of them; larger sets are
+// replaced by a uniform sample whose counts are scaled up, which overestimates the states left
+// after deduplicating. The time of each job on the sample is scaled up as well.
+pub fn estimate_plan<S: State>(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &VerifyOptions,
+) -> VerifyPlan {
+    if options.strategy().enumerates() {
+        return estimate_enumeration::<S>(n, cmp, options);
+    }
+    let (dsu, mut samples) = initial_states::<S>(n, options);
+    let mut planner = Planner::new(cmp, dsu);
+    let mut lens = samples.iter().map(|v| v.len() as f64).collect::<Vec<_>>();
+    let mut rng = 0x2545f4914f6cdd1du64;
+    let mut random = move |m: usize| {
+        rng ^= rng << 13;
+        rng ^= rng >> 7;
+        rng ^= rng << 17;
+        (rng % m as u64) as usize
+    };
+    let bytes = std::mem::size_of::<(S, S)>() as f64;
+    let mut steps = vec![];
+    loop {
+        let estimated = lens.iter().map(|&l| l as usize).collect::<Vec<_>>();
+        let Some(job) = planner.next_job(n, cmp, &estimated, options.strategy()) else {
+            break;
+        };
+        let all = lens.iter().sum::<f64>();
+        let step = match job {
+            VerifyJob::Cmp { root, ref cmp_part } => {
+                let scale = lens[root] / samples[root].len().max(1) as f64;
+                let begin_time = Instant::now();
+                let mut expanded = expand_states(&samples[root], cmp_part);
+                let sample_generated = expanded.len().max(2) as f64;
+                let generated = expanded.len() as f64 * scale;
+                // Like the verifier, deduplicate only when the job adds states
+                if expanded.len() > samples[root].len() {
+                    expanded.par_sort_unstable();
+                    expanded.dedup();
+                }
+                // Sorting dominates: scale the time of the sample by n log n
+                let time = begin_time.elapsed().as_nanos() as f64 / 1e6
+                    * scale
+                    * generated.max(2.0).log2()
+                    / sample_generated.log2();
+                let len = expanded.len() as f64 * scale;
+                while expanded.len() > PLAN_SAMPLE {
+                    let i = random(expanded.len());
+                    expanded.swap_remove(i);
+                }
+                lens[root] = len;
+                samples[root] = expanded;
+                PlanStep {
+                    job: format!("Cmp, root: {root}, cmp: {}", cmp_part.len()),
+                    len,
+                    generated,
+                    memory: (all + 2.0 * generated) * bytes,
+                    time,
+                    exact: scale == 1.0,
+                }
+            }
+            VerifyJob::Combine {
+                root_master,
+                root_slave,
+            } => {
+                let len = lens[root_master] * lens[root_slave];
+                let (master, slave) = (&samples[root_master], &samples[root_slave]);
+                let pairs = master.len() * slave.len();
+                let exact = pairs as f64 == len;
+                let unite = |i: usize| {
+                    let ((sz, so), (mz, mo)) = (slave[i / master.len()], master[i % master.len()]);
+                    (sz | mz, so | mo)
+                };
+                // Distinct pairs, repeated ones would look like duplicates in the next job
+                let united = if pairs <= PLAN_SAMPLE {
+                    (0..pairs).map(unite).collect::<Vec<_>>()
+                } else {
+                    let mut picked = std::collections::HashSet::new();
+                    while picked.len() < PLAN_SAMPLE {
+                        picked.insert(random(pairs));
+                    }
+                    picked.into_iter().map(unite).collect::<Vec<_>>()
+                };
+                // Writing the product dominates: time a copy of the sample
+                let begin_time = Instant::now();
+                let united = united.par_iter().copied().collect::<Vec<_>>();
+                let time =
+                    begin_time.elapsed().as_nanos() as f64 / 1e6 * len / united.len().max(1) as f64;
+                lens[root_master] = len;
+                lens[root_slave] = 0.0;
+                samples[root_master] = united;
+                samples[root_slave] = vec![];
+                PlanStep {
+                    job: format!("Combine, root: ({root_master},{root_slave})"),
+                    len,
+                    generated: len,
+                    memory: (all + len) * bytes,
+                    time,
+                    exact,
+                }
+            }
+        };
+        steps.push(step);
+    }
+    VerifyPlan {
+        strategy: options.strategy().name().to_string(),
+        memory: steps.iter().map(|s| s.memory).fold(0.0, f64::max),
+        time: steps.iter().map(|s| s.time).sum(),
+        steps,
+    }
+}
+
+pub fn is_sorting_network_future_v2<S: State>(
+    pool: Arc<threadpool::ThreadPool>,
+    n: usize,
+    cmp: Arc<Vec<(usize, usize)>>,
+    options: Arc<VerifyOptions>,
+    timeout: Option<Duration>,
+) -> JobResultFuture<S> {
+    debug_assert!(2 <= n && n <= S::BITS as _);
+    resume_sorting_network_future_v2(pool, Checkpoint::new(n, &cmp, &options), timeout)
+}
+
+// Continue a verification from a checkpoint, the timeout counts from now
+pub fn resume_sorting_network_future_v2<S: State>(
+    pool: Arc<threadpool::ThreadPool>,
+    checkpoint: Checkpoint<S>,
+    timeout: Option<Duration>,
+) -> JobResultFuture<S> {
+    let (progress_tx, progress_rx) = mpsc::channel::<JobProgress<S>>();
+    let cancel_state = Arc::new(AtomicBool::new(false));
+    let checkpoint_state = Arc::new(Mutex::new(None));
+    execute_job_v2(
+        pool,
+        progress_tx,
+        Arc::clone(&cancel_state),
+        Arc::clone(&checkpoint_state),
+        checkpoint,
+        timeout,
+    );
+    JobResultFuture {
+        progress_rx,
+        cancel_state,
+        checkpoint_state,
+    }
+}
+
+// Remove the comparators that never fire, then verify again until nothing changes.
+// `poll` sees the round, the comparators of the round and each progress, or None when none
+// came within `interval`; it returns false to cancel. Ok(None) means cancelled.
+pub fn minimize_network<S: State>(
+    pool: Arc<threadpool::ThreadPool>,
+    n: usize,
+    mut cmp: Vec<(usize, usize)>,
+    options: Arc<VerifyOptions>,
+    interval: Duration,
+    mut poll: impl FnMut(usize, &[(usize, usize)], Option<&JobProgress<S>>) -> bool,
+) -> Result<Option<Vec<(usize, usize)>>, String> {
+    for round in 1.. {
+        let mut last_progress = JobResult::<S>::new(n, &cmp);
+        let mut future = is_sorting_network_future_v2::<S>(
+            Arc::clone(&pool),
+            n,
+            Arc::new(cmp.clone()),
+            Arc::clone(&options),
+            None,
+        );
+        loop {
+            let progress = match future.recv_progress_timeout(interval) {
+                Ok(progress) => Some(progress),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err("error: the verification ended without a result".to_string());
+                }
+            };
+            if !poll(round, &cmp, progress.as_ref()) {
+                future.cancel();
+                return Ok(None);
+            }
+            match progress {
+                Some(JobProgress::Progress(progress)) => last_progress = progress,
+                Some(JobProgress::Cancel) => return Ok(None),
+                Some(JobProgress::Done) => break,
+                Some(JobProgress::Error(e)) => return Err(e),
+                Some(JobProgress::Timeout(_)) => unreachable!("minimize runs without a timeout"),
+                Some(JobProgress::Log(_)) | None => {}
+            }
+        }
+        let unused = last_progress.get_unused();
+        let unused_count = unused.iter().filter(|&&u| u).count();
+        let log = format!(
+            "Minimize, round: {round}, unused_cmp: {unused_count}/{cmp_count}",
+            cmp_count = cmp.len(),
+        );
+        if !poll(round, &cmp, Some(&JobProgress::Log(log))) {
+            return Ok(None);
+        }
+        if unused_count == 0 {
+            break;
+        }
+        cmp = cmp
+            .iter()
+            .zip(unused.iter())
+            .filter_map(|(&c, &u)| if u { None } else { Some(c) })
+            .collect();
+    }
+    Ok(Some(cmp))
+}
+
+// Verify on the current thread and report each progress to `on_progress` as it comes, for a
+// platform without threads such as wasm in a Web Worker. Rayon falls back to the current thread
+// there as well.
+pub fn verify_on_current_thread<S: State>(
+    checkpoint: Checkpoint<S>,
+    timeout: Option<Duration>,
+    on_progress: impl Fn(JobProgress<S>),
+) {
+    let cancel_state = Arc::new(AtomicBool::new(false));
+    let checkpoint_state = Mutex::new(None);
+    let sink = Callback(on_progress);
+    // The callback never fails
+    verify_worker(&sink, &cancel_state, &checkpoint_state, checkpoint, timeout).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::test_support::{
+        XorShift, counting, random_network, verify, without_one,
+    };
+    use crate::sorting_network_gen::gen_batcher;
+    use crate::threadpool::ThreadPool;
+
+    // The first progress is sent before any job, so the cancel is seen at the first job boundary
+    #[test]
+    fn cancel() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let cmp = gen_batcher(64);
+        let mut future = is_sorting_network_future_v2::<u64>(
+            pool,
+            64,
+            Arc::new(cmp),
+            Arc::new(counting()),
+            None,
+        );
+        assert!(matches!(
+            future.recv_progress().unwrap(),
+            JobProgress::Progress(_)
+        ));
+        future.cancel();
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Cancel => break,
+                JobProgress::Log(_) | JobProgress::Progress(_) => {}
+                JobProgress::Done => panic!("not cancelled"),
+                JobProgress::Error(e) => panic!("{e}"),
+                JobProgress::Timeout(_) => panic!("timeout"),
+            }
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let cmp = gen_batcher(64);
+        let mut future = is_sorting_network_future_v2::<u64>(
+            pool,
+            64,
+            Arc::new(cmp),
+            Arc::new(counting()),
+            Some(Duration::ZERO),
+        );
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Timeout(result) => {
+                    assert!(result.progress < result.progress_all);
+                    break;
+                }
+                JobProgress::Log(_) | JobProgress::Progress(_) => {}
+                JobProgress::Done => panic!("no timeout"),
+                JobProgress::Cancel => panic!("cancelled"),
+                JobProgress::Error(e) => panic!("{e}"),
+            }
+        }
+    }
+
+    // A comparator beyond n panics in the worker, which reports it instead of hanging up
+    #[test]
+    fn worker_panic() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let mut future = is_sorting_network_future_v2::<u64>(
+            pool,
+            4,
+            Arc::new(vec![(0, 1), (2, 9)]),
+            Arc::new(VerifyOptions::default()),
+            None,
+        );
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Error(e) => {
+                    assert!(e.contains("panic"), "{e}");
+                    break;
+                }
+                JobProgress::Log(_) | JobProgress::Progress(_) => {}
+                JobProgress::Done => panic!("no panic"),
+                JobProgress::Cancel => panic!("cancelled"),
+                JobProgress::Timeout(_) => panic!("timeout"),
+            }
+        }
+    }
+    // Beyond 64 channels only the u128 state holds a network
+    #[test]
+    fn wide_networks() {
+        let mut rng = XorShift(0x3c6ef372fe94f82b);
+        for n in [65, 100, 128] {
+            let cmp = gen_batcher(n);
+            let result = verify::<u128>(n, &cmp, &VerifyOptions::default());
+            assert!(result.is_sorting_network(), "n: {n}");
+            assert!(result.counterexample.is_none(), "n: {n}");
+            let broken = without_one(&mut rng, &cmp);
+            let result = verify::<u128>(n, &broken, &VerifyOptions::default());
+            assert!(!result.is_sorting_network(), "n: {n}");
+            let c = result.counterexample.unwrap();
+            assert!(c.output.windows(2).any(|w| w[0] > w[1]), "n: {n}");
+            for ((i, j), c) in result.witnesses {
+                assert!(c.output[i] == 1 && c.output[j] == 0, "n: {n}, ({i}, {j})");
+            }
+        }
+    }
+
+    // Each witness has the fewest ones among all inputs leaving its pair unsorted
+    #[test]
+    fn minimal_witnesses() {
+        let mut rng = XorShift(0x94d049bb133111eb);
+        for _ in 0..300 {
+            let n = 2 + rng.next(9);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            let mut options = VerifyOptions::default();
+            if rng.next(2) == 0 {
+                options.sorted_blocks.push((0, n / 2));
+            }
+            let result = verify::<u64>(n, &cmp, &options);
+            let clauses = options.clauses();
+            let mut lightest = vec![None::<u32>; n * n];
+            for x in 0..1u64 << n {
+                if !clauses.iter().all(|&clause| match clause {
+                    Precondition::Fixed(c, v) => (x >> c) & 1 == v as u64,
+                    Precondition::LessEq(a, b) => (x >> a) & 1 <= (x >> b) & 1,
+                }) {
+                    continue;
+                }
+                let y = apply_network(&cmp, x);
+                for (i, j) in (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))) {
+                    if (y >> i) & 1 == 1 && (y >> j) & 1 == 0 {
+                        let w = &mut lightest[i * n + j];
+                        *w = Some(w.map_or(x.count_ones(), |w| w.min(x.count_ones())));
+                    }
+                }
+            }
+            let case = format!("n: {n}, cmp: {cmp:?}, options: {options:?}");
+            let pairs = result.get_unsorted_allpairs();
+            assert_eq!(result.witnesses.len(), pairs.len(), "{case}");
+            for ((i, j), c) in result.witnesses {
+                assert!(c.output[i] == 1 && c.output[j] == 0, "({i}, {j}), {case}");
+                let ones = c.input.iter().filter(|&&x| x != 0).count() as u32;
+                assert_eq!(Some(ones), lightest[i * n + j], "({i}, {j}), {case}");
+            }
+        }
+    }
+
+    // Repeated comparators never fire, the minimized network sorts with all of them used
+    #[test]
+    fn minimize() {
+        let cmp = gen_batcher(8);
+        let padded = [&cmp[..], &cmp[..4], &cmp[..]].concat();
+        let pool = Arc::new(ThreadPool::new(1));
+        let mut rounds = 0;
+        let minimized = minimize_network::<u64>(
+            pool,
+            8,
+            padded.clone(),
+            Arc::new(VerifyOptions::default()),
+            Duration::from_millis(100),
+            |round, _, _| {
+                rounds = round;
+                true
+            },
+        )
+        .unwrap()
+        .unwrap();
+        assert!(minimized.len() < padded.len());
+        assert!(minimized.len() <= cmp.len());
+        assert!(rounds >= 2);
+        let result = verify::<u64>(8, &minimized, &VerifyOptions::default());
+        assert!(result.is_sorting_network());
+        assert!(result.used.iter().all(|&u| u));
+    }
+
+    // The worker refuses to count where the u128 counts overflow
+    #[test]
+    fn count_limit() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let mut future = is_sorting_network_future_v2::<u128>(
+            pool,
+            128,
+            Arc::new(gen_batcher(128)),
+            Arc::new(counting()),
+            None,
+        );
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Error(e) => {
+                    assert!(e.contains("127"), "{e}");
+                    break;
+                }
+                JobProgress::Progress(_) | JobProgress::Log(_) => {}
+                progress => panic!("{progress:?}"),
+            }
+        }
+    }
+
+    // A checkpoint taken after the first job resumes to the result of an uninterrupted run
+    #[test]
+    fn checkpoint_resume() {
+        let mut rng = XorShift(0xa0761d6478bd642f);
+        let n = 64;
+        let cmp = without_one(&mut rng, &gen_batcher(n));
+        let expected = verify::<u64>(n, &cmp, &counting());
+        let path = std::env::temp_dir().join(format!("sortnet-checkpoint-{}", std::process::id()));
+        let pool = Arc::new(ThreadPool::new(1));
+        let mut future = is_sorting_network_future_v2::<u64>(
+            Arc::clone(&pool),
+            n,
+            Arc::new(cmp.clone()),
+            Arc::new(counting()),
+            None,
+        );
+        let mut requested = false;
+        let mut saved = false;
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Log(log) if log.starts_with("Checkpoint,") => saved = true,
+                JobProgress::Log(_) if !requested => {
+                    future.checkpoint(path.clone());
+                    requested = true;
+                }
+                JobProgress::Progress(_) | JobProgress::Log(_) => {}
+                JobProgress::Done => break,
+                progress => panic!("{progress:?}"),
+            }
+        }
+        assert!(saved);
+        let checkpoint = Checkpoint::<u64>::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(checkpoint.header.job > 0);
+        assert_eq!(checkpoint.header.cmp, cmp);
+        let mut future = resume_sorting_network_future_v2(pool, checkpoint, None);
+        let mut last = None;
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Progress(progress) => last = Some(progress),
+                JobProgress::Log(_) => {}
+                JobProgress::Done => break,
+                progress => panic!("{progress:?}"),
+            }
+        }
+        let result = last.unwrap();
+        assert_eq!(result.used, expected.used);
+        assert_eq!(result.unsorted, expected.unsorted);
+        assert_eq!(result.failure_count, expected.failure_count);
+        assert_eq!(result.progress, result.progress_all);
+    }
+
+    // Run to the end, or to the error of a pass over the budget
+    fn verify_within(
+        n: usize,
+        cmp: &[(usize, usize)],
+        options: &VerifyOptions,
+        budget: usize,
+    ) -> Result<JobResult<u64>, String> {
+        let options = VerifyOptions {
+            memory_budget: Some(budget),
+            ..options.clone()
+        };
+        let pool = Arc::new(ThreadPool::new(1));
+        let cmp = Arc::new(cmp.to_vec());
+        let mut future = is_sorting_network_future_v2::<u64>(pool, n, cmp, Arc::new(options), None);
+        let mut last = None;
+        loop {
+            match future.recv_progress().unwrap() {
+                JobProgress::Progress(progress) => last = Some(progress),
+                JobProgress::Log(_) => {}
+                JobProgress::Done => return Ok(last.unwrap()),
+                JobProgress::Error(e) => {
+                    assert_eq!(last.unwrap().log, e);
+                    return Err(e);
+                }
+                progress => panic!("{progress:?}"),
+            }
+        }
+    }
+
+    // The witness and count passes hold more per state than the verification, so the least
+    // budget of the verification stops them
+    #[test]
+    fn memory_budget() {
+        let mut rng = XorShift(0xe7037ed1a0b428db);
+        let cmp = gen_batcher(16);
+        let broken = without_one(&mut rng, &cmp);
+        let e = verify_within(16, &cmp, &VerifyOptions::default(), 1).unwrap_err();
+        assert!(
+            e.contains("pass: verify") && e.ends_with("budget: 1B"),
+            "{e}"
+        );
+        for (cmp, options, pass) in [
+            (&cmp, counting(), "pass: count"),
+            (&broken, VerifyOptions::default(), "pass: witness"),
+        ] {
+            // Least budget that the verification pass gets through
+            let (mut lo, mut hi) = (1, 1 << 30);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                match verify_within(16, cmp, &options, mid) {
+                    Err(e) if e.contains("pass: verify") => lo = mid + 1,
+                    _ => hi = mid,
+                }
+            }
+            let e = verify_within(16, cmp, &options, lo).unwrap_err();
+            assert!(e.contains(pass), "{e}");
+            assert!(verify_within(16, cmp, &options, 1 << 30).is_ok());
+        }
+    }
+
+    #[test]
+    fn byte_units() {
+        assert_eq!(format_bytes(0), "0B");
+        assert_eq!(format_bytes(1023), "1023B");
+        assert_eq!(format_bytes(1536), "1.5KiB");
+        assert_eq!(format_bytes(8 << 30), "8.0GiB");
+    }
+
+    // While the states are few the plan is exact, and follows the jobs of the verification
+    #[test]
+    fn plan_steps() {
+        let mut rng = XorShift(0x8ebc6af09c88c6e3);
+        for _ in 0..100 {
+            let n = 2 + rng.next(11);
+            let m = rng.next(4 * n);
+            let cmp = random_network(&mut rng, n, m);
+            let strategy = STRATEGIES[rng.next(STRATEGIES.len())];
+            let options = VerifyOptions {
+                strategy: strategy.name().to_string(),
+                ..Default::default()
+            };
+            let plan = estimate_plan::<u64>(n, &cmp, &options);
+            // The enumeration is one step over all inputs
+            if strategy.enumerates() {
+                assert_eq!(plan.steps.len(), 1);
+                assert_eq!(plan.steps[0].len, (1u64 << n) as f64);
+                continue;
+            }
+            let pool = Arc::new(ThreadPool::new(1));
+            let mut future = is_sorting_network_future_v2::<u64>(
+                pool,
+                n,
+                Arc::new(cmp.clone()),
+                Arc::new(options),
+                None,
+            );
+            // (job, root, generated, len) of each job as logged
+            let mut jobs = vec![];
+            loop {
+                let log = match future.recv_progress().unwrap() {
+                    JobProgress::Progress(progress) => progress.log,
+                    JobProgress::Log(log) => log,
+                    JobProgress::Done => break,
+                    progress => panic!("{progress:?}"),
+                };
+                let field = |name: &str| {
+                    let start = log.find(name).unwrap() + name.len();
+                    log[start..].split(", ").next().unwrap().to_string()
+                };
+                if log.starts_with("AppliedCE,") {
+                    let len = field("len: ");
+                    let lens = len.split("=>").collect::<Vec<_>>();
+                    jobs.push(("Cmp", field("root: "), lens[1].parse(), lens[2].parse()));
+                } else if log.starts_with("Combining,") {
+                    let len = field("len: ");
+                    let united = len.split("=>").nth(1).unwrap().parse();
+                    jobs.push(("Combine", field("root: "), united.clone(), united));
+                }
+            }
+            let case = format!("n: {n}, cmp: {cmp:?}, strategy: {}", strategy.name());
+            assert_eq!(plan.steps.len(), jobs.len(), "{case}");
+            for (step, (job, root, generated, len)) in plan.steps.iter().zip(jobs) {
+                assert!(step.exact, "{case}");
+                assert!(step.job.starts_with(job), "{}, {case}", step.job);
+                assert!(
+                    step.job.contains(&format!("root: {root}")),
+                    "{}, {case}",
+                    step.job
+                );
+                // The verifier already deduplicates the new states of each chunk
+                assert!(step.generated >= generated.unwrap(), "{}, {case}", step.job);
+                assert_eq!(Ok(step.len), len, "{}, {case}", step.job);
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// A network in the formats of other tools. Procon, SorterHunter and layers are read back by
+// parse_network; the pairs of the others are 0-based.
+use crate::sorting_network_parse::gen_procon;
+use crate::sorting_network_svg::gen_layers;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    // N M, then the 1-based a and b of each comparator on two lines
+    Procon,
+    // The bracketed pair list, e.g. [(0,2),(1,3),(0,1),(2,3),(1,2)]
+    SorterHunter,
+    // {"n":4,"comparators":[[0,2],...],"depth":3,"layers":[[[0,2],[1,3]],...]}
+    Json,
+    // The pairs of a layer on each line, e.g. 0:2 1:3
+    Layers,
+}
+
+pub const EXPORT_FORMATS: [ExportFormat; 4] = [
+    ExportFormat::Procon,
+    ExportFormat::SorterHunter,
+    ExportFormat::Json,
+    ExportFormat::Layers,
+];
+
+impl ExportFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Procon => "Procon",
+            ExportFormat::SorterHunter => "SorterHunter",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Layers => "Layers",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NetworkJson {
+    pub n: usize,
+    pub comparators: Vec<(usize, usize)>,
+    pub depth: usize,
+    pub layers: Vec<Vec<(usize, usize)>>,
+}
+
+// `cmp` is a valid network of n channels, as parse_network returns
+pub fn export_network(n: usize, cmp: &[(usize, usize)], format: ExportFormat) -> String {
+    let layers = gen_layers(n, cmp);
+    let pairs = |cmp: &[(usize, usize)], f: fn(&(usize, usize)) -> String, sep| {
+        cmp.iter().map(f).collect::<Vec<_>>().join(sep)
+    };
+    match format {
+        ExportFormat::Procon => gen_procon(n, cmp),
+        ExportFormat::SorterHunter => {
+            format!("[{}]\n", pairs(cmp, |(a, b)| format!("({a},{b})"), ","))
+        }
+        ExportFormat::Json => {
+            let json = NetworkJson {
+                n,
+                comparators: cmp.to_vec(),
+                depth: layers.len(),
+                layers: layers.into_iter().map(|l| cmp[l].to_vec()).collect(),
+            };
+            serde_json::to_string(&json).unwrap() + "\n"
+        }
+        ExportFormat::Layers => layers
+            .into_iter()
+            .map(|l| pairs(&cmp[l], |(a, b)| format!("{a}:{b}"), " ") + "\n")
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::test_support::{XorShift, random_network};
+    use crate::sorting_network_parse::parse_network;
+    use crate::sorting_network_svg::gen_svg_pos;
+
+    #[test]
+    fn export_formats() {
+        let mut rng = XorShift(0x2127599bf4325c37);
+        for _ in 0..100 {
+            let n = 2 + rng.next(30);
+            let m = 1 + rng.next(4 * n);
+            let mut cmp = random_network(&mut rng, n, m);
+            // The largest channel gives n to the pair formats
+            cmp.push((0, n - 1));
+            let layers = gen_svg_pos(n, &cmp).layers;
+            for format in EXPORT_FORMATS {
+                let text = export_network(n, &cmp, format);
+                if format == ExportFormat::Json {
+                    let json: NetworkJson = serde_json::from_str(&text).unwrap();
+                    assert_eq!((json.n, &json.comparators), (n, &cmp));
+                    assert_eq!(json.depth, layers.len());
+                    assert_eq!(json.layers.concat(), cmp);
+                    continue;
+                }
+                assert_eq!(
+                    parse_network(&text),
+                    Ok((n, cmp.len(), cmp.clone())),
+                    "{format:?}: {text}"
+                );
+            }
+            let text = export_network(n, &cmp, ExportFormat::Layers);
+            assert_eq!(text.lines().count(), layers.len());
+        }
+    }
+}
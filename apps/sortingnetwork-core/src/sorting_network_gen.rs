@@ -0,0 +1,111 @@
+// Sorting network generators
+
+pub fn gen_bubble_max(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    for p in 0..(2 * n - 3) {
+        for i in ((p & 1)..((p + 1).min(2 * n - p - 2))).step_by(2) {
+            cmp.push((i, i + 1));
+        }
+    }
+    cmp
+}
+pub fn gen_bubble_min(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    for p in 0..(2 * n - 3) {
+        for i in (n.abs_diff(p + 2)..(n - 1)).step_by(2) {
+            cmp.push((i, i + 1));
+        }
+    }
+    cmp
+}
+pub fn gen_oddeven(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    for p in 0..n {
+        for i in ((p & 1)..(n - 1)).step_by(2) {
+            cmp.push((i, i + 1));
+        }
+    }
+    cmp
+}
+pub fn triangular_indices(n: usize) -> (usize, usize) {
+    let r = ((8 * n + 1).isqrt() - 1) / 2;
+    (r, n - r * (r + 1) / 2)
+}
+pub fn gen_bitonic(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    let r = (n).next_power_of_two().ilog2() as usize;
+    for d in 0..(r * (r + 1) / 2) {
+        let (m, p) = triangular_indices(d);
+        for i in 0..n {
+            let j = if p == 0 {
+                i ^ ((2 << m) - 1)
+            } else {
+                i ^ (1 << (m - p))
+            };
+            if i < j && j < n {
+                cmp.push((i, j));
+            }
+        }
+    }
+    cmp
+}
+pub fn gen_batcher(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    let r = (n).next_power_of_two().ilog2() as usize;
+    for d in 0..(r * (r + 1) / 2) {
+        let (m, p) = triangular_indices(d);
+        for i in 0..n {
+            let j = if p == 0 {
+                i ^ (1 << m)
+            } else {
+                let (scale, boxmask) = (m - p, (2usize << p) - 1);
+                let sn = (i >> scale) & boxmask;
+                if sn == 0 || sn == boxmask {
+                    i
+                } else if (sn & 1) == 0 {
+                    i - (1 << scale)
+                } else {
+                    i + (1 << scale)
+                }
+            };
+            if i < j && j < n {
+                cmp.push((i, j));
+            }
+        }
+    }
+    cmp
+}
+pub fn gen_pairwise(n: usize) -> Vec<(usize, usize)> {
+    assert!((2..=128).contains(&n));
+    let mut cmp = Vec::new();
+    let r = (n).next_power_of_two().ilog2() as usize;
+    for d in 0..(r * (r + 1) / 2) {
+        let (m, p) = if d < r {
+            (0, d)
+        } else {
+            let (tm, tp) = triangular_indices(d - r);
+            (tm + 1, tp)
+        };
+        for i in 0..n {
+            let j = if m == 0 {
+                i ^ (1 << p)
+            } else {
+                let dj = (1 << (r - p - 1)) - (1 << (r - m - 1));
+                if ((i >> (r - m - 1)) & 1) == 0 {
+                    if i >= dj { i - dj } else { i }
+                } else {
+                    i + dj
+                }
+            };
+            if i < j && j < n {
+                cmp.push((i, j));
+            }
+        }
+    }
+    cmp
+}
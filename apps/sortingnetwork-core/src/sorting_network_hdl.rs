@@ -0,0 +1,570 @@
+// Hardware sorters generated from a network: signal l{d}_{i} holds channel i after layer d, the
+// comparators of a layer work in parallel, and each layer may end with a pipeline register.
+use crate::sorting_network_svg::gen_layers;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// The testbench simulates 2^n inputs
+pub const TESTBENCH_MAX_N: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HdlLanguage {
+    Verilog,
+    Vhdl,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HdlOptions {
+    pub language: HdlLanguage,
+    // Default of the WIDTH parameter, the bits of an unsigned value
+    pub width: usize,
+    // A register after each layer, the latency is then the depth in clock cycles
+    pub pipeline: bool,
+    // Append a testbench of all 0-1 inputs
+    pub testbench: bool,
+}
+
+// How each channel leaves a layer: the min or max of a comparator, or unchanged
+#[derive(Clone, Copy)]
+enum Channel {
+    Min(usize, usize),
+    Max(usize, usize),
+    Pass(usize),
+}
+
+// `cmp` is a valid network of n channels, as parse_network returns
+pub fn gen_hdl(n: usize, cmp: &[(usize, usize)], options: &HdlOptions) -> Result<String, String> {
+    if options.width == 0 {
+        return Err("the width must be at least 1".to_string());
+    }
+    if options.testbench && n > TESTBENCH_MAX_N {
+        return Err(format!("the testbench needs n <= {TESTBENCH_MAX_N}"));
+    }
+    let layers = gen_layers(n, cmp)
+        .into_iter()
+        .map(|l| {
+            let mut channels = (0..n).map(Channel::Pass).collect::<Vec<_>>();
+            for &(a, b) in &cmp[l] {
+                channels[a] = Channel::Min(a, b);
+                channels[b] = Channel::Max(a, b);
+            }
+            channels
+        })
+        .collect::<Vec<_>>();
+    let latency = if options.pipeline { layers.len() } else { 0 };
+    let summary = format!(
+        "Sorts {n} unsigned WIDTH-bit values ascending with a sorting network of {} comparators\n\
+         in {} layers, {}.\n\
+         Value i is bits i*WIDTH to (i+1)*WIDTH-1 of data_in; data_out has the smallest at 0.",
+        cmp.len(),
+        layers.len(),
+        if options.pipeline {
+            format!("with a register after each layer (latency {latency} cycles)")
+        } else {
+            "combinational".to_string()
+        }
+    );
+    Ok(match options.language {
+        HdlLanguage::Verilog => gen_verilog(n, &layers, &summary, options, latency),
+        HdlLanguage::Vhdl => gen_vhdl(n, &layers, &summary, options, latency),
+    })
+}
+
+fn comment(prefix: &str, text: &str) -> String {
+    text.lines()
+        .map(|line| format!("{prefix} {line}\n"))
+        .collect()
+}
+
+fn gen_verilog(
+    n: usize,
+    layers: &[Vec<Channel>],
+    summary: &str,
+    options: &HdlOptions,
+    latency: usize,
+) -> String {
+    let width = options.width;
+    let mut code = comment("//", summary);
+    writeln!(
+        code,
+        "module sort{n} #(\n    parameter WIDTH = {width}\n) ("
+    )
+    .unwrap();
+    if options.pipeline {
+        code.push_str("    input wire clk,\n");
+    }
+    writeln!(code, "    input wire [{n}*WIDTH-1:0] data_in,").unwrap();
+    writeln!(code, "    output wire [{n}*WIDTH-1:0] data_out\n);").unwrap();
+    for i in 0..n {
+        writeln!(
+            code,
+            "    wire [WIDTH-1:0] l0_{i} = data_in[{i}*WIDTH +: WIDTH];"
+        )
+        .unwrap();
+    }
+    for (d, channels) in (1..).zip(layers) {
+        let p = d - 1;
+        let expr = |c: Channel| match c {
+            Channel::Min(a, b) => format!("l{p}_{a} < l{p}_{b} ? l{p}_{a} : l{p}_{b}"),
+            Channel::Max(a, b) => format!("l{p}_{a} < l{p}_{b} ? l{p}_{b} : l{p}_{a}"),
+            Channel::Pass(i) => format!("l{p}_{i}"),
+        };
+        writeln!(code, "    // layer {d}").unwrap();
+        if options.pipeline {
+            let names = (0..n).map(|i| format!("l{d}_{i}")).collect::<Vec<_>>();
+            writeln!(code, "    reg [WIDTH-1:0] {};", names.join(", ")).unwrap();
+            code.push_str("    always @(posedge clk) begin\n");
+            for (i, &c) in channels.iter().enumerate() {
+                writeln!(code, "        l{d}_{i} <= {};", expr(c)).unwrap();
+            }
+            code.push_str("    end\n");
+        } else {
+            for (i, &c) in channels.iter().enumerate() {
+                writeln!(code, "    wire [WIDTH-1:0] l{d}_{i} = {};", expr(c)).unwrap();
+            }
+        }
+    }
+    let outputs = (0..n)
+        .rev()
+        .map(|i| format!("l{}_{i}", layers.len()))
+        .collect::<Vec<_>>();
+    writeln!(code, "    assign data_out = {{{}}};", outputs.join(", ")).unwrap();
+    code.push_str("endmodule\n");
+    if options.testbench {
+        // The combinational sorter settles within the #1
+        let (clk_port, clock, wait) = if options.pipeline {
+            (
+                ".clk(clk), ",
+                "    reg clk = 0;\n    always #5 clk = ~clk;\n",
+                "            repeat (LATENCY) @(posedge clk);\n",
+            )
+        } else {
+            ("", "", "")
+        };
+        write!(
+            code,
+            r#"
+// By the 0-1 principle, sorting every 0-1 input proves the network.
+// Icarus: iverilog -o sort{n}_tb sort{n}.v && vvp sort{n}_tb
+module sort{n}_tb;
+    localparam N = {n};
+    localparam WIDTH = {width};
+    localparam LATENCY = {latency};
+{clock}    reg [N*WIDTH-1:0] data_in;
+    wire [N*WIDTH-1:0] data_out;
+    integer mask;
+    integer errors = 0;
+
+    sort{n} #(.WIDTH(WIDTH)) dut ({clk_port}.data_in(data_in), .data_out(data_out));
+
+    // Channel j of the input is bit j of m
+    function [N*WIDTH-1:0] zero_one;
+        input integer m;
+        integer j;
+        begin
+            zero_one = 0;
+            for (j = 0; j < N; j = j + 1)
+                zero_one[j*WIDTH] = m[j];
+        end
+    endfunction
+
+    // The ones of m on the top channels
+    function [N*WIDTH-1:0] sorted;
+        input integer m;
+        integer j, ones;
+        begin
+            ones = 0;
+            for (j = 0; j < N; j = j + 1)
+                ones = ones + m[j];
+            sorted = 0;
+            for (j = N - ones; j < N; j = j + 1)
+                sorted[j*WIDTH] = 1'b1;
+        end
+    endfunction
+
+    initial begin
+        for (mask = 0; mask < (1 << N); mask = mask + 1) begin
+            data_in = zero_one(mask);
+{wait}            #1;
+            if (data_out !== sorted(mask)) begin
+                $display("FAIL: input %h, output %h", data_in, data_out);
+                errors = errors + 1;
+            end
+        end
+        if (errors == 0)
+            $display("PASS: all %0d 0-1 inputs sorted", 1 << N);
+        $finish;
+    end
+endmodule
+"#
+        )
+        .unwrap();
+    }
+    code
+}
+
+fn gen_vhdl(
+    n: usize,
+    layers: &[Vec<Channel>],
+    summary: &str,
+    options: &HdlOptions,
+    latency: usize,
+) -> String {
+    let width = options.width;
+    let d_max = layers.len();
+    let signals = |prefix: &str, d: usize| {
+        (0..n)
+            .map(|i| format!("{prefix}{d}_{i}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut code = comment("--", summary);
+    code.push_str("library ieee;\nuse ieee.std_logic_1164.all;\nuse ieee.numeric_std.all;\n\n");
+    writeln!(code, "entity sort{n} is").unwrap();
+    writeln!(
+        code,
+        "    generic (\n        WIDTH : positive := {width}\n    );"
+    )
+    .unwrap();
+    code.push_str("    port (\n");
+    if options.pipeline {
+        code.push_str("        clk      : in  std_logic;\n");
+    }
+    writeln!(
+        code,
+        "        data_in  : in  std_logic_vector({n}*WIDTH-1 downto 0);"
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "        data_out : out std_logic_vector({n}*WIDTH-1 downto 0)\n    );"
+    )
+    .unwrap();
+    code.push_str("end entity;\n\n");
+    writeln!(code, "architecture rtl of sort{n} is").unwrap();
+    code.push_str("    subtype value is unsigned(WIDTH-1 downto 0);\n");
+    for d in 0..=d_max {
+        writeln!(code, "    signal {} : value;", signals("l", d)).unwrap();
+    }
+    // The comparator outputs a register takes in, conditional assignments are concurrent
+    if options.pipeline {
+        for (d, channels) in (1..).zip(layers) {
+            let names = channels
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !matches!(c, Channel::Pass(_)))
+                .map(|(i, _)| format!("c{d}_{i}"))
+                .collect::<Vec<_>>();
+            writeln!(code, "    signal {} : value;", names.join(", ")).unwrap();
+        }
+    }
+    code.push_str("begin\n");
+    for i in 0..n {
+        writeln!(
+            code,
+            "    l0_{i} <= unsigned(data_in({}*WIDTH-1 downto {i}*WIDTH));",
+            i + 1
+        )
+        .unwrap();
+    }
+    for (d, channels) in (1..).zip(layers) {
+        let p = d - 1;
+        writeln!(code, "    -- layer {d}").unwrap();
+        let target = |i| {
+            if options.pipeline {
+                format!("c{d}_{i}")
+            } else {
+                format!("l{d}_{i}")
+            }
+        };
+        for (i, &c) in channels.iter().enumerate() {
+            match c {
+                Channel::Min(a, b) => writeln!(
+                    code,
+                    "    {} <= l{p}_{a} when l{p}_{a} < l{p}_{b} else l{p}_{b};",
+                    target(i)
+                ),
+                Channel::Max(a, b) => writeln!(
+                    code,
+                    "    {} <= l{p}_{b} when l{p}_{a} < l{p}_{b} else l{p}_{a};",
+                    target(i)
+                ),
+                Channel::Pass(_) if options.pipeline => Ok(()),
+                Channel::Pass(i) => writeln!(code, "    l{d}_{i} <= l{p}_{i};"),
+            }
+            .unwrap();
+        }
+        if options.pipeline {
+            code.push_str("    process (clk)\n    begin\n        if rising_edge(clk) then\n");
+            for (i, &c) in channels.iter().enumerate() {
+                match c {
+                    Channel::Pass(i) => writeln!(code, "            l{d}_{i} <= l{p}_{i};"),
+                    _ => writeln!(code, "            l{d}_{i} <= c{d}_{i};"),
+                }
+                .unwrap();
+            }
+            code.push_str("        end if;\n    end process;\n");
+        }
+    }
+    for i in 0..n {
+        writeln!(
+            code,
+            "    data_out({}*WIDTH-1 downto {i}*WIDTH) <= std_logic_vector(l{d_max}_{i});",
+            i + 1
+        )
+        .unwrap();
+    }
+    code.push_str("end architecture;\n");
+    if options.testbench {
+        let (clk_port, clk_signal, wait) = if options.pipeline {
+            (
+                "clk => clk, ",
+                "    signal clk : std_logic := '0';\n",
+                "            for k in 1 to LATENCY loop\n                wait until rising_edge(clk);\n            end loop;\n",
+            )
+        } else {
+            ("", "", "")
+        };
+        let clock = if options.pipeline {
+            "    clk <= not clk after 5 ns when not done else clk;\n\n"
+        } else {
+            ""
+        };
+        write!(
+            code,
+            r#"
+-- By the 0-1 principle, sorting every 0-1 input proves the network.
+-- GHDL: ghdl -a sort{n}.vhd && ghdl -e sort{n}_tb && ghdl -r sort{n}_tb
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity sort{n}_tb is
+end entity;
+
+architecture sim of sort{n}_tb is
+    constant N : positive := {n};
+    constant WIDTH : positive := {width};
+    constant LATENCY : natural := {latency};
+{clk_signal}    signal data_in, data_out : std_logic_vector(N*WIDTH-1 downto 0);
+    signal done : boolean := false;
+begin
+    dut : entity work.sort{n}
+        generic map (WIDTH => WIDTH)
+        port map ({clk_port}data_in => data_in, data_out => data_out);
+
+{clock}    process
+        variable input, expected : std_logic_vector(N*WIDTH-1 downto 0);
+        variable ones : natural;
+        variable errors : natural := 0;
+    begin
+        for mask in 0 to 2**N - 1 loop
+            input := (others => '0');
+            ones := 0;
+            for j in 0 to N - 1 loop
+                if (mask / 2**j) mod 2 = 1 then
+                    input(j*WIDTH) := '1';
+                    ones := ones + 1;
+                end if;
+            end loop;
+            -- The ones on the top channels
+            expected := (others => '0');
+            for j in N - ones to N - 1 loop
+                expected(j*WIDTH) := '1';
+            end loop;
+            data_in <= input;
+{wait}            wait for 1 ns;
+            if data_out /= expected then
+                report "FAIL: input " & integer'image(mask) severity error;
+                errors := errors + 1;
+            end if;
+        end loop;
+        if errors = 0 then
+            report "PASS: all " & integer'image(2**N) & " 0-1 inputs sorted";
+        end if;
+        done <= true;
+        wait;
+    end process;
+end architecture;
+"#
+        )
+        .unwrap();
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::test_support::{XorShift, without_one};
+    use crate::sorting_network_gen::gen_batcher;
+
+    // Directory of the simulations, removed when the test ends, also on a failure
+    struct TempDir(std::path::PathBuf);
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("sortnet-hdl-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    // Simulates the testbench of a sorter, whether it passes
+    fn run_hdl(dir: &TempDir, name: &str, n: usize, language: HdlLanguage, code: &str) -> bool {
+        let dir = &dir.0;
+        let tb = format!("sort{n}_tb");
+        let workdir = format!("--workdir={}", dir.display());
+        let (src, steps) = match language {
+            HdlLanguage::Verilog => (
+                format!("{name}.v"),
+                vec![vec!["iverilog", "-o", name, "SRC"], vec!["vvp", name]],
+            ),
+            HdlLanguage::Vhdl => (
+                format!("{name}.vhd"),
+                vec![
+                    vec!["ghdl", "-a", &workdir, "SRC"],
+                    vec!["ghdl", "-e", &workdir, &tb],
+                    vec!["ghdl", "-r", &workdir, &tb],
+                ],
+            ),
+        };
+        std::fs::write(dir.join(&src), code).unwrap();
+        let mut log = String::new();
+        for step in steps {
+            let args = step[1..].iter().map(|&a| if a == "SRC" { &src } else { a });
+            let output = std::process::Command::new(step[0])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap_or_else(|e| panic!("{}: {e}", step[0]));
+            log = String::from_utf8_lossy(&output.stdout).into_owned()
+                + &String::from_utf8_lossy(&output.stderr);
+            assert!(output.status.success(), "{name}: {log}\n{code}");
+        }
+        log.contains("PASS") && !log.contains("FAIL")
+    }
+
+    // The comparators of each layer as the code compares them, `l{p}_{a} < l{p}_{b}` on both the
+    // min and the max, and the number of registered channels
+    fn structure(code: &str, language: HdlLanguage) -> (Vec<Vec<(usize, usize)>>, usize) {
+        let signal = |token: &str| {
+            let (d, i) = token.strip_prefix('l')?.split_once('_')?;
+            Some((d.parse::<usize>().ok()?, i.parse::<usize>().ok()?))
+        };
+        let mut layers = vec![];
+        for line in code.lines() {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            for w in tokens.windows(3).filter(|w| w[1] == "<") {
+                let (Some((p, a)), Some((q, b))) = (signal(w[0]), signal(w[2])) else {
+                    continue;
+                };
+                assert_eq!(p, q, "{line}");
+                if layers.len() <= p {
+                    layers.resize(p + 1, vec![]);
+                }
+                layers[p].push((a, b));
+            }
+        }
+        let indent = match language {
+            HdlLanguage::Verilog => "        l",
+            HdlLanguage::Vhdl => "            l",
+        };
+        let registers = code
+            .lines()
+            .filter(|line| line.starts_with(indent) && line.contains(" <= "))
+            .count();
+        (layers, registers)
+    }
+
+    // The layers, the comparators and the pipeline registers of the code follow the network,
+    // without a simulator
+    #[test]
+    fn hdl() {
+        let mut rng = XorShift(0xbb67ae8584caa73b);
+        for n in [3, 5, 8, 16, 33] {
+            for cmp in [gen_batcher(n), without_one(&mut rng, &gen_batcher(n))] {
+                let layers = gen_layers(n, &cmp);
+                for language in [HdlLanguage::Verilog, HdlLanguage::Vhdl] {
+                    for pipeline in [false, true] {
+                        let options = HdlOptions {
+                            language,
+                            width: 4,
+                            pipeline,
+                            testbench: false,
+                        };
+                        let code = gen_hdl(n, &cmp, &options).unwrap();
+                        let (found, registers) = structure(&code, language);
+                        assert_eq!(found.len(), layers.len(), "{code}");
+                        for (layer, found) in layers.iter().zip(found) {
+                            // The min and the max of each comparator
+                            let mut expected = cmp[layer.clone()].to_vec();
+                            expected.sort_unstable();
+                            let mut pairs = found.clone();
+                            pairs.sort_unstable();
+                            pairs.dedup();
+                            assert_eq!(pairs, expected, "{code}");
+                            assert_eq!(found.len(), 2 * expected.len(), "{code}");
+                        }
+                        let expected = if pipeline { n * layers.len() } else { 0 };
+                        assert_eq!(registers, expected, "{code}");
+                    }
+                }
+            }
+        }
+        let mut options = HdlOptions {
+            language: HdlLanguage::Verilog,
+            width: 0,
+            pipeline: false,
+            testbench: false,
+        };
+        assert!(gen_hdl(4, &gen_batcher(4), &options).is_err());
+        options.width = 8;
+        assert!(gen_hdl(64, &gen_batcher(64), &options).is_ok());
+        options.testbench = true;
+        let n = TESTBENCH_MAX_N + 1;
+        assert!(gen_hdl(n, &gen_batcher(n), &options).is_err());
+    }
+
+    // Runs the testbenches, which needs Icarus Verilog and GHDL:
+    // cargo test -p sortingnetwork-core -- --ignored hdl_simulation
+    #[test]
+    #[ignore = "needs iverilog and ghdl"]
+    fn hdl_simulation() {
+        let mut rng = XorShift(0xbb67ae8584caa73b);
+        let dir = TempDir::new();
+        for n in [3, 5, 8] {
+            let cmp = gen_batcher(n);
+            let broken = without_one(&mut rng, &cmp);
+            for language in [HdlLanguage::Verilog, HdlLanguage::Vhdl] {
+                for pipeline in [false, true] {
+                    let options = HdlOptions {
+                        language,
+                        width: 4,
+                        pipeline,
+                        testbench: true,
+                    };
+                    let code = gen_hdl(n, &cmp, &options).unwrap();
+                    let (module, tb) = match language {
+                        HdlLanguage::Verilog => {
+                            (format!("module sort{n} "), format!("module sort{n}_tb;"))
+                        }
+                        HdlLanguage::Vhdl => (
+                            format!("entity sort{n} is"),
+                            format!("entity sort{n}_tb is"),
+                        ),
+                    };
+                    assert!(code.contains(&module) && code.contains(&tb), "{code}");
+                    let name = format!("sort{n}_{language:?}_{pipeline}");
+                    assert!(run_hdl(&dir, &name, n, language, &code), "{name}\n{code}");
+                    let code = gen_hdl(n, &broken, &options).unwrap();
+                    let ok = run_hdl(&dir, &format!("{name}_broken"), n, language, &code);
+                    assert!(!ok, "{name} broken\n{code}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,572 @@
+// Branchless sorting kernels generated from a network: the values are loaded into locals, each
+// comparator becomes a min/max pair, and the comparators are grouped by the layers of the drawing
+// so the independent ones of a layer sit together.
+// The SIMD kernel sorts many arrays at once rather than one array faster: each chunk of arrays is
+// transposed so that a vector holds one channel of several arrays, and a comparator is then a
+// lane-wise min/max of two vectors. There are no in-register shuffles; the transpose in memory
+// costs a pass over each chunk, which pays off for many small arrays, not for a single one.
+use crate::sorting_network_svg::gen_layers;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// The 0-1 self-test runs 2^n inputs
+pub const SELF_TEST_MAX_N: usize = 24;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelLanguage {
+    C,
+    Rust,
+    // Many arrays at once, one per vector lane
+    RustSimd,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KernelOptions {
+    pub language: KernelLanguage,
+    // Element type, empty for the default: `int` in C, the generic `T: Ord + Copy` in Rust, i32
+    // in SIMD. Any other type is a primitive specialisation; SIMD takes i32, u32 and f32.
+    pub elem: String,
+    // Append a test of all 0-1 inputs: a main in C, a #[test] in Rust
+    pub self_test: bool,
+}
+
+// `cmp` is a valid network of n channels, as parse_network returns
+pub fn gen_kernel(
+    n: usize,
+    cmp: &[(usize, usize)],
+    options: &KernelOptions,
+) -> Result<String, String> {
+    let elem = options.elem.trim();
+    let elem = match (elem, options.language) {
+        ("", KernelLanguage::C) => "int",
+        ("", KernelLanguage::Rust) => "T",
+        ("", KernelLanguage::RustSimd) => "i32",
+        _ => elem,
+    };
+    let valid = |w: &str| {
+        w.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !w.starts_with(|c: char| c.is_ascii_digit())
+    };
+    // C types may take several words, e.g. unsigned long
+    let words = elem.split_ascii_whitespace().collect::<Vec<_>>();
+    if !words.iter().all(|w| valid(w)) || words.len() > 1 && options.language != KernelLanguage::C {
+        return Err(format!("invalid element type: {elem}"));
+    }
+    let elem = words.join(" ");
+    if cmp.is_empty() {
+        return Err("the network has no comparators".to_string());
+    }
+    if options.self_test && n > SELF_TEST_MAX_N {
+        return Err(format!("the self-test needs n <= {SELF_TEST_MAX_N}"));
+    }
+    let layers = gen_layers(n, cmp)
+        .into_iter()
+        .map(|l| &cmp[l])
+        .collect::<Vec<_>>();
+    let summary = format!(
+        "Sorts {n} values ascending with a sorting network of {} comparators in {} layers",
+        cmp.len(),
+        layers.len()
+    );
+    Ok(match options.language {
+        KernelLanguage::C => gen_c(n, &elem, &layers, &summary, options.self_test),
+        KernelLanguage::Rust => gen_rust(n, cmp, &elem, &layers, &summary, options.self_test),
+        KernelLanguage::RustSimd => gen_rust_simd(n, &elem, &layers, &summary, options.self_test)?,
+    })
+}
+
+fn gen_c(
+    n: usize,
+    elem: &str,
+    layers: &[&[(usize, usize)]],
+    summary: &str,
+    self_test: bool,
+) -> String {
+    // For the fixed-width element types
+    let mut code = "#include <stdint.h>\n\n".to_string();
+    writeln!(code, "/* {summary} */").unwrap();
+    writeln!(code, "static void sort{n}({elem} *v)\n{{").unwrap();
+    for i in 0..n {
+        writeln!(code, "    {elem} x{i} = v[{i}];").unwrap();
+    }
+    for (d, layer) in layers.iter().enumerate() {
+        writeln!(code, "    /* layer {} */", d + 1).unwrap();
+        for &(a, b) in layer.iter() {
+            writeln!(
+                code,
+                "    {{ {elem} lo = x{a} < x{b} ? x{a} : x{b}; x{b} = x{a} < x{b} ? x{b} : x{a}; x{a} = lo; }}"
+            )
+            .unwrap();
+        }
+    }
+    for i in 0..n {
+        writeln!(code, "    v[{i}] = x{i};").unwrap();
+    }
+    code.push_str("}\n");
+    if self_test {
+        // By the 0-1 principle, sorting every 0-1 input proves the network
+        write!(
+            code,
+            r#"
+#include <stdio.h>
+
+int main(void)
+{{
+    for (unsigned long mask = 0; mask < 1ul << {n}; mask++) {{
+        {elem} v[{n}];
+        int ones = 0;
+        for (int i = 0; i < {n}; i++) {{
+            v[i] = (mask >> i & 1) ? ({elem})1 : ({elem})0;
+            ones += mask >> i & 1;
+        }}
+        sort{n}(v);
+        for (int i = 0; i < {n}; i++) {{
+            if (v[i] != (i >= {n} - ones ? ({elem})1 : ({elem})0)) {{
+                printf("sort{n}: not sorted for input %#lx\n", mask);
+                return 1;
+            }}
+        }}
+    }}
+    printf("sort{n}: all %lu 0-1 inputs sorted\n", 1ul << {n});
+    return 0;
+}}
+"#
+        )
+        .unwrap();
+    }
+    code
+}
+
+fn gen_rust(
+    n: usize,
+    cmp: &[(usize, usize)],
+    elem: &str,
+    layers: &[&[(usize, usize)]],
+    summary: &str,
+    self_test: bool,
+) -> String {
+    let mut used = vec![false; n];
+    for &(a, b) in cmp {
+        used[a] = true;
+        used[b] = true;
+    }
+    let (generic, test_elem) = if elem == "T" {
+        ("<T: Ord + Copy>", "u8")
+    } else {
+        ("", elem)
+    };
+    let locals = (0..n)
+        .map(|i| format!("{}x{i}", if used[i] { "mut " } else { "" }))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let xs = (0..n)
+        .map(|i| format!("x{i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut code = String::new();
+    writeln!(code, "// {summary}").unwrap();
+    writeln!(code, "pub fn sort{n}{generic}(v: &mut [{elem}; {n}]) {{").unwrap();
+    writeln!(code, "    let [{locals}] = *v;").unwrap();
+    for (d, layer) in layers.iter().enumerate() {
+        writeln!(code, "    // layer {}", d + 1).unwrap();
+        for &(a, b) in layer.iter() {
+            writeln!(code, "    (x{a}, x{b}) = (x{a}.min(x{b}), x{a}.max(x{b}));").unwrap();
+        }
+    }
+    writeln!(code, "    *v = [{xs}];").unwrap();
+    code.push_str("}\n");
+    if self_test {
+        write!(
+            code,
+            r#"
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    // By the 0-1 principle, sorting every 0-1 input proves the network
+    #[test]
+    fn sort{n}_zero_one() {{
+        let (zero, one) = ({test_elem}::from(false), {test_elem}::from(true));
+        for mask in 0u64..1 << {n} {{
+            let mut v = [zero; {n}];
+            for (i, x) in v.iter_mut().enumerate() {{
+                if mask >> i & 1 == 1 {{
+                    *x = one;
+                }}
+            }}
+            let ones = mask.count_ones() as usize;
+            sort{n}(&mut v);
+            for (i, &x) in v.iter().enumerate() {{
+                assert!(x == if i >= {n} - ones {{ one }} else {{ zero }}, "input {{mask:#x}}");
+            }}
+        }}
+    }}
+}}
+"#
+        )
+        .unwrap();
+    }
+    code
+}
+
+// The intrinsics of a vector type: name, load, store, min and max
+struct Isa {
+    feature: &'static str,
+    lanes: usize,
+    vector: &'static str,
+    load: &'static str,
+    store: &'static str,
+    min: &'static str,
+    max: &'static str,
+}
+
+fn simd_isas(elem: &str) -> Option<[Isa; 2]> {
+    let (avx2, sse41) = match elem {
+        "i32" => (
+            ("_mm256_min_epi32", "_mm256_max_epi32"),
+            ("_mm_min_epi32", "_mm_max_epi32"),
+        ),
+        "u32" => (
+            ("_mm256_min_epu32", "_mm256_max_epu32"),
+            ("_mm_min_epu32", "_mm_max_epu32"),
+        ),
+        "f32" => (
+            ("_mm256_min_ps", "_mm256_max_ps"),
+            ("_mm_min_ps", "_mm_max_ps"),
+        ),
+        _ => return None,
+    };
+    let float = elem == "f32";
+    Some([
+        Isa {
+            feature: "avx2",
+            lanes: 8,
+            vector: if float { "__m256" } else { "__m256i" },
+            load: if float {
+                "_mm256_loadu_ps"
+            } else {
+                "_mm256_loadu_si256"
+            },
+            store: if float {
+                "_mm256_storeu_ps"
+            } else {
+                "_mm256_storeu_si256"
+            },
+            min: avx2.0,
+            max: avx2.1,
+        },
+        Isa {
+            feature: "sse4.1",
+            lanes: 4,
+            vector: if float { "__m128" } else { "__m128i" },
+            load: if float {
+                "_mm_loadu_ps"
+            } else {
+                "_mm_loadu_si128"
+            },
+            store: if float {
+                "_mm_storeu_ps"
+            } else {
+                "_mm_storeu_si128"
+            },
+            min: sse41.0,
+            max: sse41.1,
+        },
+    ])
+}
+
+// One array per lane: the chunks of arrays are transposed to columns, so a comparator is the
+// min/max of two whole vectors and the layers need no shuffles
+fn gen_rust_simd(
+    n: usize,
+    elem: &str,
+    layers: &[&[(usize, usize)]],
+    summary: &str,
+    self_test: bool,
+) -> Result<String, String> {
+    let isas =
+        simd_isas(elem).ok_or_else(|| format!("SIMD kernels take i32, u32 or f32, not {elem}"))?;
+    let mut code = String::new();
+    writeln!(code, "// {summary},").unwrap();
+    code.push_str(
+        "// in each of many arrays at once: the arrays go to the lanes of AVX2 or SSE4.1 vectors,\n\
+         // chosen at run time, or to the portable code on other CPUs.\n",
+    );
+    writeln!(
+        code,
+        "pub fn sort{n}_arrays(arrays: &mut [[{elem}; {n}]]) {{"
+    )
+    .unwrap();
+    writeln!(code, "    #[cfg(target_arch = \"x86_64\")]\n    {{").unwrap();
+    for isa in &isas {
+        let name = isa.feature.replace('.', "");
+        write!(
+            code,
+            r#"        if is_x86_feature_detected!("{feature}") {{
+            // SAFETY: the CPU has {feature}
+            return sort{n}_chunks::<{lanes}>(arrays, |c| unsafe {{ sort{n}_{name}(c) }});
+        }}
+"#,
+            feature = isa.feature,
+            lanes = isa.lanes,
+        )
+        .unwrap();
+    }
+    code.push_str("    }\n");
+    writeln!(
+        code,
+        "    sort{n}_chunks::<8>(arrays, sort{n}_portable::<8>);\n}}"
+    )
+    .unwrap();
+    write!(
+        code,
+        r#"
+// Transposes each chunk of L arrays to the columns the kernel sorts; a short last chunk is padded
+fn sort{n}_chunks<const L: usize>(
+    arrays: &mut [[{elem}; {n}]],
+    kernel: impl Fn(&mut [[{elem}; L]; {n}]),
+) {{
+    for chunk in arrays.chunks_mut(L) {{
+        let mut columns = [[{elem}::default(); L]; {n}];
+        for (l, array) in chunk.iter().enumerate() {{
+            for (column, &x) in columns.iter_mut().zip(array.iter()) {{
+                column[l] = x;
+            }}
+        }}
+        kernel(&mut columns);
+        for (l, array) in chunk.iter_mut().enumerate() {{
+            for (column, x) in columns.iter().zip(array.iter_mut()) {{
+                *x = column[l];
+            }}
+        }}
+    }}
+}}
+
+fn sort{n}_minmax<const L: usize>(columns: &mut [[{elem}; L]; {n}], a: usize, b: usize) {{
+    let (lo, hi) = columns.split_at_mut(b);
+    for (x, y) in lo[a].iter_mut().zip(hi[0].iter_mut()) {{
+        (*x, *y) = ((*x).min(*y), (*x).max(*y));
+    }}
+}}
+
+fn sort{n}_portable<const L: usize>(columns: &mut [[{elem}; L]; {n}]) {{
+"#
+    )
+    .unwrap();
+    for (d, layer) in layers.iter().enumerate() {
+        writeln!(code, "    // layer {}", d + 1).unwrap();
+        for &(a, b) in layer.iter() {
+            writeln!(code, "    sort{n}_minmax(columns, {a}, {b});").unwrap();
+        }
+    }
+    code.push_str("}\n");
+    for isa in &isas {
+        let name = isa.feature.replace('.', "");
+        let lanes = isa.lanes;
+        // The float loads take element pointers, the integer ones vector pointers
+        let ptr = if elem == "f32" { "f32" } else { isa.vector };
+        write!(
+            code,
+            r#"
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "{feature}")]
+unsafe fn sort{n}_{name}(columns: &mut [[{elem}; {lanes}]; {n}]) {{
+    use std::arch::x86_64::*;
+    let p = columns.as_mut_ptr();
+    // SAFETY: each column is one unaligned vector
+    unsafe {{
+"#,
+            feature = isa.feature,
+        )
+        .unwrap();
+        for i in 0..n {
+            writeln!(
+                code,
+                "        let x{i} = {load}(p.add({i}) as *const {ptr});",
+                load = isa.load
+            )
+            .unwrap();
+        }
+        for (d, layer) in layers.iter().enumerate() {
+            writeln!(code, "        // layer {}", d + 1).unwrap();
+            for &(a, b) in layer.iter() {
+                writeln!(
+                    code,
+                    "        let (x{a}, x{b}) = ({min}(x{a}, x{b}), {max}(x{a}, x{b}));",
+                    min = isa.min,
+                    max = isa.max
+                )
+                .unwrap();
+            }
+        }
+        for i in 0..n {
+            writeln!(
+                code,
+                "        {store}(p.add({i}) as *mut {ptr}, x{i});",
+                store = isa.store
+            )
+            .unwrap();
+        }
+        code.push_str("    }\n}\n");
+    }
+    if self_test {
+        write!(
+            code,
+            r#"
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    // Every 0-1 input from `start`, one per array
+    fn zero_one(start: u64) -> Vec<[{elem}; {n}]> {{
+        (start..(start + 4096).min(1 << {n}))
+            .map(|mask| std::array::from_fn(|i| {elem}::from(mask >> i & 1 == 1)))
+            .collect()
+    }}
+
+    fn check(path: &str, start: u64, arrays: &[[{elem}; {n}]]) {{
+        for (mask, array) in (start..).zip(arrays) {{
+            let ones = mask.count_ones() as usize;
+            for (i, &x) in array.iter().enumerate() {{
+                assert!(x == {elem}::from(i >= {n} - ones), "{{path}}: input {{mask:#x}}");
+            }}
+        }}
+    }}
+
+    // By the 0-1 principle, sorting every 0-1 input proves the network; each kernel the CPU
+    // runs is checked
+    #[test]
+    fn sort{n}_zero_one() {{
+        for start in (0..1u64 << {n}).step_by(4096) {{
+            let mut arrays = zero_one(start);
+            sort{n}_chunks::<8>(&mut arrays, sort{n}_portable::<8>);
+            check("portable", start, &arrays);
+            let mut arrays = zero_one(start);
+            sort{n}_arrays(&mut arrays);
+            check("dispatch", start, &arrays);
+            #[cfg(target_arch = "x86_64")]
+            {{
+"#
+        )
+        .unwrap();
+        for isa in &isas {
+            let name = isa.feature.replace('.', "");
+            write!(
+                code,
+                r#"                if is_x86_feature_detected!("{feature}") {{
+                    let mut arrays = zero_one(start);
+                    sort{n}_chunks::<{lanes}>(&mut arrays, |c| unsafe {{ sort{n}_{name}(c) }});
+                    check("{feature}", start, &arrays);
+                }}
+"#,
+                feature = isa.feature,
+                lanes = isa.lanes,
+            )
+            .unwrap();
+        }
+        code.push_str("            }\n        }\n    }\n}\n");
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::test_support::{XorShift, without_one};
+    use crate::sorting_network_gen::gen_batcher;
+
+    // Directory of the compiled kernels, removed when the test ends, also on a failure
+    struct TempDir(std::path::PathBuf);
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("sortnet-kernel-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    // Compiles a kernel with its self-test and runs it, None when the compiler is missing
+    fn run_kernel(dir: &TempDir, name: &str, language: KernelLanguage, code: &str) -> Option<bool> {
+        let dir = &dir.0;
+        let exe = dir.join(name);
+        let (src, mut compiler) = match language {
+            KernelLanguage::C => {
+                let mut cc = std::process::Command::new("cc");
+                cc.args(["-std=c99", "-O1", "-Wall", "-Werror"]);
+                (dir.join(format!("{name}.c")), cc)
+            }
+            KernelLanguage::Rust | KernelLanguage::RustSimd => {
+                let mut rustc = std::process::Command::new("rustc");
+                rustc.args(["--edition", "2021", "--test", "-D", "warnings"]);
+                (dir.join(format!("{name}.rs")), rustc)
+            }
+        };
+        std::fs::write(&src, code).unwrap();
+        let output = match compiler.arg(&src).arg("-o").arg(&exe).output() {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => panic!("{e}"),
+        };
+        assert!(
+            output.status.success(),
+            "{name}: {}\n{code}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let status = std::process::Command::new(&exe).output().unwrap().status;
+        Some(status.success())
+    }
+
+    #[test]
+    fn kernels() {
+        let mut rng = XorShift(0x6a09e667f3bcc909);
+        let dir = TempDir::new();
+        for n in [3, 5, 8, 12] {
+            let cmp = gen_batcher(n);
+            let broken = without_one(&mut rng, &cmp);
+            for (language, elem) in [
+                (KernelLanguage::C, ""),
+                (KernelLanguage::C, "unsigned long"),
+                (KernelLanguage::C, "uint16_t"),
+                (KernelLanguage::C, "double"),
+                (KernelLanguage::Rust, ""),
+                (KernelLanguage::Rust, "u32"),
+                (KernelLanguage::Rust, "i64"),
+                (KernelLanguage::RustSimd, ""),
+                (KernelLanguage::RustSimd, "u32"),
+                (KernelLanguage::RustSimd, "f32"),
+            ] {
+                let options = KernelOptions {
+                    language,
+                    elem: elem.to_string(),
+                    self_test: true,
+                };
+                let name = format!("sort{n}_{language:?}_{}", elem.replace(' ', "_"));
+                let code = gen_kernel(n, &cmp, &options).unwrap();
+                let Some(ok) = run_kernel(&dir, &name, language, &code) else {
+                    continue;
+                };
+                assert!(ok, "{name}\n{code}");
+                let code = gen_kernel(n, &broken, &options).unwrap();
+                let ok = run_kernel(&dir, &format!("{name}_broken"), language, &code).unwrap();
+                assert!(!ok, "{name} broken\n{code}");
+            }
+        }
+        let options = KernelOptions {
+            language: KernelLanguage::Rust,
+            elem: "unsigned long".to_string(),
+            self_test: false,
+        };
+        assert!(gen_kernel(4, &gen_batcher(4), &options).is_err());
+        let options = KernelOptions {
+            language: KernelLanguage::RustSimd,
+            elem: "i64".to_string(),
+            self_test: false,
+        };
+        assert!(gen_kernel(4, &gen_batcher(4), &options).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-pub const OPT_NET: [(&str, u8, usize, usize, &[u8], &[u8]); 118] = [
+// Name, n, comparators, depth, and the channels a and b of each comparator
+pub type OptNet = (&'static str, u8, usize, usize, &'static [u8], &'static [u8]);
+
+pub const OPT_NET: [OptNet; 118] = [
     ("N2L1D1", 2, 1, 1, &[0], &[1]),
     ("N3L3D3", 3, 3, 3, &[0, 0, 1], &[2, 1, 2]),
     ("N4L5D3", 4, 5, 3, &[0, 1, 0, 2, 1], &[2, 3, 1, 3, 2]),
@@ -3711,3 +3714,35 @@ pub const OPT_NET: [(&str, u8, usize, usize, &[u8], &[u8]); 118] = [
         ],
     ),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::is_sorting_network_brute;
+    use crate::sorting_network_check_brute::test_support::{
+        XorShift, assert_agree, counting, without_one,
+    };
+    use crate::sorting_network_check_v2::VerifyOptions;
+
+    #[test]
+    fn opt_net() {
+        let mut rng = XorShift(0x94d049bb133111eb);
+        for &(id, n, l, _, a, b) in OPT_NET.iter().filter(|e| e.1 as usize <= 20) {
+            let n = n as usize;
+            let cmp = a
+                .iter()
+                .zip(b.iter())
+                .map(|(&a, &b)| (a as usize, b as usize))
+                .collect::<Vec<_>>();
+            assert_eq!(cmp.len(), l, "{id}");
+            assert!(
+                is_sorting_network_brute::<u64>(n, &cmp, &VerifyOptions::default())
+                    .unwrap()
+                    .is_sorting_network(),
+                "{id}"
+            );
+            assert_agree::<u64>(n, &cmp, &counting());
+            assert_agree::<u64>(n, &without_one(&mut rng, &cmp), &counting());
+        }
+    }
+}
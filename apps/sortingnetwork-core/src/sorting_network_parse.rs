@@ -0,0 +1,691 @@
+// Reading the network text and the options of a verification, with the position of each error
+use crate::sorting_network_check_v2::{MAX_N, Precondition};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Position in the network text, 1-based line and column counted in chars
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+impl Span {
+    fn new(line: usize, text: &str, start: usize, end: usize) -> Self {
+        Span {
+            line,
+            column: text[..start].chars().count() + 1,
+            len: text[start..end].chars().count(),
+        }
+    }
+    // Empty span after the end of the line
+    fn end_of(line: usize, text: &str) -> Self {
+        Span::new(line, text, text.len(), text.len())
+    }
+}
+
+// Value a network line holds: n and m on the first line, then the a and b of each comparator
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    N,
+    M,
+    A,
+    B,
+}
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Field::N => "n",
+            Field::M => "m",
+            Field::A => "a",
+            Field::B => "b",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    MissingValue {
+        span: Span,
+        field: Field,
+    },
+    MissingLine {
+        span: Span,
+        field: Field,
+    },
+    NotInteger {
+        span: Span,
+        field: Field,
+        token: String,
+    },
+    InvalidN {
+        span: Span,
+        n: usize,
+    },
+    // Too many values point at the first extra one, too few at the end of the line
+    CountMismatch {
+        span: Span,
+        field: Field,
+        expected: usize,
+        found: usize,
+    },
+    OutOfRange {
+        span: Span,
+        field: Field,
+        comparator: usize,
+        value: usize,
+        n: usize,
+    },
+    // Points at b
+    NotAscending {
+        span: Span,
+        comparator: usize,
+        a: usize,
+        b: usize,
+    },
+    // A channel of a pair list at or above the n of its header, points at b
+    ChannelOutOfRange {
+        span: Span,
+        comparator: usize,
+        channel: usize,
+        n: usize,
+    },
+}
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match *self {
+            ParseError::Empty => Span {
+                line: 1,
+                column: 1,
+                len: 0,
+            },
+            ParseError::MissingValue { span, .. }
+            | ParseError::MissingLine { span, .. }
+            | ParseError::NotInteger { span, .. }
+            | ParseError::InvalidN { span, .. }
+            | ParseError::CountMismatch { span, .. }
+            | ParseError::OutOfRange { span, .. }
+            | ParseError::NotAscending { span, .. }
+            | ParseError::ChannelOutOfRange { span, .. } => span,
+        }
+    }
+}
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        match self {
+            ParseError::Empty => return write!(f, "empty input"),
+            ParseError::MissingLine { field, .. } => {
+                return write!(f, "line {}: missing the line of {field}", span.line);
+            }
+            _ => write!(f, "line {}, column {}: ", span.line, span.column)?,
+        }
+        match self {
+            ParseError::Empty | ParseError::MissingLine { .. } => unreachable!(),
+            ParseError::MissingValue { field, .. } => write!(f, "missing {field}"),
+            ParseError::NotInteger { field, token, .. } => {
+                write!(f, "{field} is not an integer: {token}")
+            }
+            ParseError::InvalidN { n, .. } => write!(f, "n = {n} is out of range 2..={MAX_N}"),
+            ParseError::CountMismatch {
+                field,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "expected m = {expected} values of {field}, found {found}"
+            ),
+            ParseError::OutOfRange {
+                field,
+                comparator,
+                value,
+                n,
+                ..
+            } => write!(
+                f,
+                "{field} = {value} of comparator {comparator} is out of range 1..={n}"
+            ),
+            ParseError::NotAscending {
+                comparator, a, b, ..
+            } => write!(
+                f,
+                "comparator {comparator} has a = {a} not less than b = {b}"
+            ),
+            ParseError::ChannelOutOfRange {
+                comparator,
+                channel,
+                n,
+                ..
+            } => write!(
+                f,
+                "channel {channel} of comparator {comparator} is out of range 0..{n}"
+            ),
+        }
+    }
+}
+
+// Whitespace separated tokens of a line with their spans
+fn tokens(line: usize, text: &str) -> Vec<(Span, &str)> {
+    split_tokens(line, text, |c| c.is_ascii_whitespace())
+}
+
+fn split_tokens(line: usize, text: &str, is_separator: fn(char) -> bool) -> Vec<(Span, &str)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, is_separator(c)) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push((Span::new(line, text, s, i), &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn parse_value(field: Field, (span, token): (Span, &str)) -> Result<usize, ParseError> {
+    token.parse().map_err(|_| ParseError::NotInteger {
+        span,
+        field,
+        token: token.to_string(),
+    })
+}
+
+// The values of a comparator line, with their spans
+fn parse_values(
+    lines: &mut std::str::Lines,
+    line: usize,
+    field: Field,
+    m: usize,
+) -> Result<Vec<(Span, usize)>, ParseError> {
+    let text = lines.next().ok_or(ParseError::MissingLine {
+        span: Span {
+            line,
+            column: 1,
+            len: 0,
+        },
+        field,
+    })?;
+    let tokens = tokens(line, text);
+    if tokens.len() != m {
+        return Err(ParseError::CountMismatch {
+            span: tokens
+                .get(m)
+                .map_or_else(|| Span::end_of(line, text), |t| t.0),
+            field,
+            expected: m,
+            found: tokens.len(),
+        });
+    }
+    tokens
+        .into_iter()
+        .map(|t| Ok((t.0, parse_value(field, t)?)))
+        .collect()
+}
+
+// n, the number of comparators and the 0-based comparators
+pub type ParsedNetwork = (usize, usize, Vec<(usize, usize)>);
+
+// The procon format (N M, then the 1-based a and b of each comparator on two lines), or a list of
+// 0-based pairs: the bracketed `[(0,13),(1,12),...]` of SorterHunter and bertdobbelaere's pages,
+// one pair per line (`0 13`, `0,13`, `0:13`), or a layered listing with the pairs of a layer on
+// one line. A pair list may start with a line of its n (`16` or `n=16`), otherwise n is one more
+// than its largest channel.
+pub fn parse_network(net: &str) -> Result<ParsedNetwork, ParseError> {
+    if starts_with_pair(net) {
+        return parse_pairs(net);
+    }
+    let mut lines = net.lines().collect::<Vec<_>>();
+    while lines.last().is_some_and(|text| text.trim().is_empty()) {
+        lines.pop();
+    }
+    if is_procon(&lines) {
+        return parse_procon(net);
+    }
+    // Text shaped as a procon network that is neither one nor a list of pairs is reported with
+    // the procon errors, other text with the errors of the pairs
+    match parse_pairs(net) {
+        Err(_) if procon_shape(&lines).is_some() => parse_procon(net),
+        result => result,
+    }
+}
+
+// The n of a pair list on a line of its own, a lone integer or `n=16`
+fn pairs_header(line: usize, text: &str) -> Option<(Span, &str)> {
+    let header = split_tokens(line, text, |c| c.is_ascii_whitespace() || c == '=');
+    match header[..] {
+        [value] if !text.contains('=') && value.1.bytes().all(|c| c.is_ascii_digit()) => {
+            Some(value)
+        }
+        [(_, "n" | "N"), value] if text.contains('=') => Some(value),
+        _ => None,
+    }
+}
+
+// Whether the first token after the blank lines and the header opens a bracketed pair
+fn starts_with_pair(net: &str) -> bool {
+    let mut lines = net.lines().filter(|text| !text.trim().is_empty());
+    let first = match lines.next() {
+        Some(text) if pairs_header(1, text).is_some() => lines.next(),
+        first => first,
+    };
+    first.is_some_and(|text| text.trim_start().starts_with(['(', '[']))
+}
+
+// n and m of text shaped as a procon network: a header of two integers with n >= 2, followed by
+// at most two lines
+fn procon_shape(lines: &[&str]) -> Option<(usize, usize)> {
+    let (header, rest) = lines.split_first()?;
+    let [(_, n), (_, m)] = tokens(1, header)[..] else {
+        return None;
+    };
+    let (n, m) = (n.parse().ok()?, m.parse().ok()?);
+    (n >= 2 && rest.len() <= 2).then_some((n, m))
+}
+
+// A procon network: the header, then exactly two lines of m values in 1..=n
+fn is_procon(lines: &[&str]) -> bool {
+    procon_shape(lines).is_some_and(|(n, m)| {
+        lines.len() == 3
+            && lines[1..].iter().all(|text| {
+                let values = tokens(1, text);
+                values.len() == m
+                    && values
+                        .iter()
+                        .all(|(_, v)| v.parse().is_ok_and(|v: usize| (1..=n).contains(&v)))
+            })
+    })
+}
+
+fn parse_procon(net: &str) -> Result<ParsedNetwork, ParseError> {
+    let mut lines = net.lines();
+    let text = lines.next().ok_or(ParseError::Empty)?;
+    let mut w = tokens(1, text).into_iter();
+    let missing = |field| ParseError::MissingValue {
+        span: Span::end_of(1, text),
+        field,
+    };
+    let t = w.next().ok_or_else(|| missing(Field::N))?;
+    let n = parse_value(Field::N, t)?;
+    if !(2..=MAX_N).contains(&n) {
+        return Err(ParseError::InvalidN { span: t.0, n });
+    }
+    let m = parse_value(Field::M, w.next().ok_or_else(|| missing(Field::M))?)?;
+    let a = parse_values(&mut lines, 2, Field::A, m)?;
+    let b = parse_values(&mut lines, 3, Field::B, m)?;
+    let mut cmp = Vec::with_capacity(m);
+    for (i, (&(span_a, a), &(span_b, b))) in a.iter().zip(b.iter()).enumerate() {
+        for (span, field, value) in [(span_a, Field::A, a), (span_b, Field::B, b)] {
+            if value < 1 || value > n {
+                return Err(ParseError::OutOfRange {
+                    span,
+                    field,
+                    comparator: i + 1,
+                    value,
+                    n,
+                });
+            }
+        }
+        if a >= b {
+            return Err(ParseError::NotAscending {
+                span: span_b,
+                comparator: i + 1,
+                a,
+                b,
+            });
+        }
+        cmp.push((a - 1, b - 1));
+    }
+
+    Ok((n, m, cmp))
+}
+
+// Each line holds whole pairs after the optional header, blank lines are skipped
+fn parse_pairs(net: &str) -> Result<ParsedNetwork, ParseError> {
+    let is_separator = |c: char| c.is_ascii_whitespace() || "()[]{},;:".contains(c);
+    let mut cmp = vec![];
+    let mut max = (Span::end_of(1, ""), 0);
+    let mut header = None;
+    let first = net.lines().position(|text| !text.trim().is_empty());
+    for (i, text) in net.lines().enumerate() {
+        if Some(i) == first
+            && let Some(t) = pairs_header(i + 1, text)
+        {
+            let n = parse_value(Field::N, t)?;
+            if !(2..=MAX_N).contains(&n) {
+                return Err(ParseError::InvalidN { span: t.0, n });
+            }
+            header = Some(n);
+            continue;
+        }
+        let mut w = split_tokens(i + 1, text, is_separator).into_iter();
+        while let Some(t) = w.next() {
+            let a = parse_value(Field::A, t)?;
+            let t = w.next().ok_or(ParseError::MissingValue {
+                span: Span::end_of(i + 1, text),
+                field: Field::B,
+            })?;
+            let b = parse_value(Field::B, t)?;
+            if a >= b {
+                return Err(ParseError::NotAscending {
+                    span: t.0,
+                    comparator: cmp.len() + 1,
+                    a,
+                    b,
+                });
+            }
+            if let Some(n) = header.filter(|&n| b >= n) {
+                return Err(ParseError::ChannelOutOfRange {
+                    span: t.0,
+                    comparator: cmp.len() + 1,
+                    channel: b,
+                    n,
+                });
+            }
+            if b >= max.1 {
+                max = (t.0, b);
+            }
+            cmp.push((a, b));
+        }
+    }
+    if cmp.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let n = header.unwrap_or(max.1 + 1);
+    if n > MAX_N {
+        return Err(ParseError::InvalidN { span: max.0, n });
+    }
+    Ok((n, cmp.len(), cmp))
+}
+
+// Sorted input blocks as 1-based inclusive ranges, e.g. "1-8 9-16"
+pub fn parse_sorted_blocks(n: usize, blocks: &str) -> Result<Vec<(usize, usize)>, String> {
+    let mut sorted_blocks = blocks
+        .split(|c: char| c.is_ascii_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let (l, r) = w
+                .split_once('-')
+                .ok_or_else(|| format!("invalid block {w}"))?;
+            let l: usize = l.parse().map_err(|_| format!("parseint failed {w}"))?;
+            let r: usize = r.parse().map_err(|_| format!("parseint failed {w}"))?;
+            if l < 1 || l > r || r > n {
+                return Err(format!("invalid block {w}"));
+            }
+            Ok((l - 1, r))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    sorted_blocks.sort_unstable();
+    if sorted_blocks.windows(2).any(|w| w[0].1 > w[1].0) {
+        return Err("overlapping blocks".to_string());
+    }
+    Ok(sorted_blocks)
+}
+
+// Output positions to verify, e.g. "min max", "median", "top4", "1-4 16" (1-based)
+pub fn parse_target(n: usize, target: &str) -> Result<Vec<usize>, String> {
+    let mut positions = vec![];
+    for w in target
+        .split(|c: char| c.is_ascii_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+    {
+        match w {
+            "min" => positions.push(0),
+            "max" => positions.push(n - 1),
+            "median" => positions.extend([(n - 1) / 2, n / 2]),
+            _ => {
+                let (l, r) = if let Some(k) = w.strip_prefix("top") {
+                    let k: usize = k.parse().map_err(|_| format!("parseint failed {w}"))?;
+                    ((n + 1).saturating_sub(k), n)
+                } else if let Some(k) = w.strip_prefix("bottom") {
+                    let k: usize = k.parse().map_err(|_| format!("parseint failed {w}"))?;
+                    (1, k)
+                } else if let Some((l, r)) = w.split_once('-') {
+                    let l = l.parse().map_err(|_| format!("parseint failed {w}"))?;
+                    let r = r.parse().map_err(|_| format!("parseint failed {w}"))?;
+                    (l, r)
+                } else {
+                    let p = w.parse().map_err(|_| format!("parseint failed {w}"))?;
+                    (p, p)
+                };
+                if l < 1 || l > r || r > n {
+                    return Err(format!("invalid target {w}"));
+                }
+                positions.extend(l - 1..r);
+            }
+        }
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    Ok(positions)
+}
+
+// Precondition clauses (1-based channels): "3:0" / "3:1" fixes a value,
+// "2<=5", "5>=2" and "2=5" order channels, "ksorted:4" orders every channel before the one 4 later
+pub fn parse_preconditions(n: usize, clauses: &str) -> Result<Vec<Precondition>, String> {
+    let channel = |w: &str, c: &str| -> Result<usize, String> {
+        let c: usize = c.parse().map_err(|_| format!("parseint failed {w}"))?;
+        if c < 1 || c > n {
+            return Err(format!("invalid channel {w}"));
+        }
+        Ok(c - 1)
+    };
+    let mut preconditions = vec![];
+    for w in clauses
+        .split(|c: char| c.is_ascii_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+    {
+        if let Some(k) = w.strip_prefix("ksorted:") {
+            let k: usize = k.parse().map_err(|_| format!("parseint failed {w}"))?;
+            if k < 1 {
+                return Err(format!("invalid clause {w}"));
+            }
+            preconditions.extend((k..n).map(|u| Precondition::LessEq(u - k, u)));
+        } else if let Some((c, v)) = w.split_once(':') {
+            let v = match v {
+                "0" => false,
+                "1" => true,
+                _ => return Err(format!("invalid value {w}")),
+            };
+            preconditions.push(Precondition::Fixed(channel(w, c)?, v));
+        } else if let Some((a, b)) = w.split_once("<=") {
+            preconditions.push(Precondition::LessEq(channel(w, a)?, channel(w, b)?));
+        } else if let Some((a, b)) = w.split_once(">=") {
+            preconditions.push(Precondition::LessEq(channel(w, b)?, channel(w, a)?));
+        } else if let Some((a, b)) = w.split_once('=') {
+            let (a, b) = (channel(w, a)?, channel(w, b)?);
+            preconditions.extend([Precondition::LessEq(a, b), Precondition::LessEq(b, a)]);
+        } else {
+            return Err(format!("invalid clause {w}"));
+        }
+    }
+    Ok(preconditions)
+}
+
+// Memory budget in MiB, or with a K/M/G/T suffix; unlimited if empty
+pub fn parse_memory_budget(budget: &str) -> Result<Option<usize>, String> {
+    let budget = budget.trim();
+    if budget.is_empty() {
+        return Ok(None);
+    }
+    let digits = budget
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(budget.len());
+    let (value, unit) = budget.split_at(digits);
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "k" | "kb" | "kib" => 10,
+        "" | "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        _ => return Err(format!("invalid memory budget: {budget}")),
+    };
+    let value = value
+        .parse::<usize>()
+        .map_err(|_| format!("invalid memory budget: {budget}"))?;
+    value
+        .checked_mul(1 << shift)
+        .map(Some)
+        .ok_or_else(|| format!("memory budget too large: {budget}"))
+}
+
+// Seconds, or a number with the unit s, m or h; no timeout if empty
+pub fn parse_timeout(timeout: &str) -> Result<Option<Duration>, String> {
+    let timeout = timeout.trim();
+    if timeout.is_empty() {
+        return Ok(None);
+    }
+    let digits = timeout
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(timeout.len());
+    let (value, unit) = timeout.split_at(digits);
+    let scale = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "s" | "sec" => 1,
+        "m" | "min" => 60,
+        "h" => 3600,
+        _ => return Err(format!("invalid timeout: {timeout}")),
+    };
+    let value = value
+        .parse::<u64>()
+        .map_err(|_| format!("invalid timeout: {timeout}"))?;
+    value
+        .checked_mul(scale)
+        .map(|secs| Some(Duration::from_secs(secs)))
+        .ok_or_else(|| format!("timeout too large: {timeout}"))
+}
+
+pub fn gen_procon(n: usize, cmp: &[(usize, usize)]) -> String {
+    let mut procon = String::new();
+    procon.push_str(&format!("{} {}\n", n, cmp.len()));
+    procon.push_str(
+        &cmp.iter()
+            .map(|&(i, _)| (i + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    procon.push('\n');
+    procon.push_str(
+        &cmp.iter()
+            .map(|&(_, j)| (j + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    procon.push('\n');
+    procon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_formats() {
+        let expected = Ok((4, 5, vec![(0, 2), (1, 3), (0, 1), (2, 3), (1, 2)]));
+        for net in [
+            "4 5\n1 2 1 3 2\n3 4 2 4 3\n",
+            "[(0,2),(1,3),(0,1),(2,3),(1,2)]",
+            "0 2\n1 3\n0 1\n2 3\n1 2\n",
+            "0,2\n1,3\n\n0:1\n2:3\n1:2\n",
+            "[(0,2),(1,3)]\n[(0,1),(2,3)]\n[(1,2)]\n",
+            "0 2 1 3\n0 1 2 3\n1 2\n",
+        ] {
+            assert_eq!(parse_network(net), expected, "{net:?}");
+        }
+        // A procon network with a wrong count keeps its error
+        assert!(matches!(
+            parse_network("4 3\n1 2\n3 4\n"),
+            Err(ParseError::CountMismatch { .. })
+        ));
+        // 0 is no procon value, so these are pairs; a value out of range is still a procon error
+        assert_eq!(
+            parse_network("2 4\n0 1 3 5"),
+            Ok((6, 3, vec![(2, 4), (0, 1), (3, 5)]))
+        );
+        assert_eq!(
+            parse_network("2 3\n0 1\n1 2\n"),
+            Ok((4, 3, vec![(2, 3), (0, 1), (1, 2)]))
+        );
+        assert!(matches!(
+            parse_network("4 2\n1 5\n2 3"),
+            Err(ParseError::OutOfRange { value: 5, .. })
+        ));
+        assert!(matches!(
+            parse_network("[(0,2),(1,3),(2,x)]"),
+            Err(ParseError::NotInteger {
+                field: Field::B,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_network("[(0,2),(3,1)]"),
+            Err(ParseError::NotAscending { comparator: 2, .. })
+        ));
+        assert!(matches!(
+            parse_network("[(0,200)]"),
+            Err(ParseError::InvalidN { n: 201, .. })
+        ));
+        // A pair list may give its n, above its largest channel
+        let expected = Ok((6, 2, vec![(0, 2), (1, 3)]));
+        for net in [
+            "6\n[(0,2),(1,3)]",
+            "\nn=6\n0 2\n1 3\n",
+            "N = 6\n0:2 1:3",
+            "  6  \n\n[(0,2)]\n[(1,3)]",
+        ] {
+            assert_eq!(parse_network(net), expected, "{net:?}");
+        }
+        assert!(matches!(
+            parse_network("n=3\n[(0,2),(1,3)]"),
+            Err(ParseError::ChannelOutOfRange {
+                comparator: 2,
+                channel: 3,
+                n: 3,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_network("n=1\n[(0,1)]"),
+            Err(ParseError::InvalidN { n: 1, .. })
+        ));
+        // Only a leading bracket makes a pair list, a stray one keeps the procon errors
+        assert!(matches!(
+            parse_network("4 2\n1 3\n2 (4"),
+            Err(ParseError::NotInteger {
+                field: Field::B,
+                ..
+            })
+        ));
+    }
+
+    // The span selects the offending value, counted in chars, or the end of a short line
+    #[test]
+    fn parse_error_spans() {
+        let span = |line, column, len| Span { line, column, len };
+        let cases = [
+            ("", span(1, 1, 0)),
+            ("200 1\n1\n2", span(1, 1, 3)),
+            ("4 2\n1 x\n2 4", span(2, 3, 1)),
+            ("4 2\n1 2\n3 15", span(3, 3, 2)),
+            ("4 2\n1 3\n2 2", span(3, 3, 1)),
+            ("4 2\n1 3\n2", span(3, 2, 0)),
+            ("4 2\n1 3\n2 4 1", span(3, 5, 1)),
+            ("4 2\n1 3", span(3, 1, 0)),
+            ("[(0,2),(ä,3)]", span(1, 9, 1)),
+            ("[(0,2),\n (3,1)]", span(2, 5, 1)),
+            ("0:2 1:3\n0:1 2", span(2, 6, 0)),
+            ("n=3\n[(0,2),(1,3)]", span(2, 11, 1)),
+            ("n=x\n0 1", span(1, 3, 1)),
+        ];
+        for (net, expected) in cases {
+            let e = parse_network(net).unwrap_err();
+            assert_eq!(e.span(), expected, "{net:?}: {e}");
+        }
+        assert_eq!(
+            parse_network("4 2\n1 3\n2 2").unwrap_err().to_string(),
+            "line 3, column 3: comparator 2 has a = 3 not less than b = 2"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::sorting_network_check_brute::BRUTE_MAX_N;
+use crate::sorting_network_check_v2::MAX_N;
+
+// Two components that a blocked comparator would join
+#[derive(Debug, Clone, Copy)]
+pub struct CombineCandidate {
+    // Roots of the components of the comparator inputs a < b
+    pub root_a: usize,
+    pub root_b: usize,
+    // Channels of each component
+    pub size_a: usize,
+    pub size_b: usize,
+    // States of each component so far
+    pub len_a: usize,
+    pub len_b: usize,
+    // Comparators left that would have both inputs in the united component
+    pub constraints: usize,
+}
+impl CombineCandidate {
+    fn union_key(&self) -> (usize, usize, usize) {
+        (self.size_a + self.size_b, self.root_a, self.root_b)
+    }
+    fn product(&self) -> u128 {
+        self.len_a as u128 * self.len_b as u128
+    }
+}
+
+// Order in which the verification combines the channel components. The comparators are
+// applied layer by layer within the components, and when none can be applied the strategy
+// picks the components to combine.
+pub trait VerifyStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Largest n the strategy is practical for
+    fn max_n(&self) -> usize {
+        MAX_N
+    }
+    // Whether all components are combined before the first comparator
+    fn combine_first(&self) -> bool {
+        false
+    }
+    // Whether every 0-1 input is run through the network instead of the states
+    fn enumerates(&self) -> bool {
+        false
+    }
+    // Index of the candidate to combine, `candidates` is not empty
+    fn pick(&self, candidates: &[CombineCandidate]) -> usize;
+}
+
+// Greedy default: the smallest union of components
+pub struct SmallestUnion;
+impl VerifyStrategy for SmallestUnion {
+    fn name(&self) -> &'static str {
+        "smallest-union"
+    }
+    fn pick(&self, candidates: &[CombineCandidate]) -> usize {
+        (0..candidates.len())
+            .min_by_key(|&i| candidates[i].union_key())
+            .unwrap()
+    }
+}
+
+// The fewest states after combining
+pub struct SmallestProduct;
+impl VerifyStrategy for SmallestProduct {
+    fn name(&self) -> &'static str {
+        "smallest-product"
+    }
+    fn pick(&self, candidates: &[CombineCandidate]) -> usize {
+        (0..candidates.len())
+            .min_by_key(|&i| (candidates[i].product(), candidates[i].union_key()))
+            .unwrap()
+    }
+}
+
+// The union that unlocks the most comparators
+pub struct MostConstrained;
+impl VerifyStrategy for MostConstrained {
+    fn name(&self) -> &'static str {
+        "most-constrained"
+    }
+    fn pick(&self, candidates: &[CombineCandidate]) -> usize {
+        (0..candidates.len())
+            .min_by_key(|&i| {
+                (
+                    std::cmp::Reverse(candidates[i].constraints),
+                    candidates[i].union_key(),
+                )
+            })
+            .unwrap()
+    }
+}
+
+// One component of all channels before the first comparator, so nothing is combined later.
+// Its states are still symbolic: a free channel stays one state until a comparator splits it,
+// and the states grow with the comparators of the network rather than with 2^n.
+pub struct SingleComponent;
+impl VerifyStrategy for SingleComponent {
+    fn name(&self) -> &'static str {
+        "single-component"
+    }
+    fn combine_first(&self) -> bool {
+        true
+    }
+    fn pick(&self, _candidates: &[CombineCandidate]) -> usize {
+        0
+    }
+}
+
+// All 2^n inputs through the network, as the brute-force checker does. Slow beyond a few dozen
+// channels, but it needs no states, and its counts are exact by construction. The witnesses are
+// still found from the states of a single component.
+pub struct Enumerate;
+impl VerifyStrategy for Enumerate {
+    fn name(&self) -> &'static str {
+        "enumerate"
+    }
+    fn max_n(&self) -> usize {
+        BRUTE_MAX_N
+    }
+    fn combine_first(&self) -> bool {
+        true
+    }
+    fn enumerates(&self) -> bool {
+        true
+    }
+    fn pick(&self, _candidates: &[CombineCandidate]) -> usize {
+        0
+    }
+}
+
+pub const STRATEGIES: [&dyn VerifyStrategy; 5] = [
+    &SmallestUnion,
+    &SmallestProduct,
+    &MostConstrained,
+    &SingleComponent,
+    &Enumerate,
+];
+
+// The default strategy if the name is empty
+pub fn strategy_by_name(name: &str) -> Option<&'static dyn VerifyStrategy> {
+    if name.is_empty() {
+        return Some(STRATEGIES[0]);
+    }
+    STRATEGIES.iter().copied().find(|s| s.name() == name)
+}
+
+pub fn parse_strategy(n: usize, name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let strategy = strategy_by_name(name).ok_or_else(|| {
+        format!(
+            "unknown strategy: {name} (expected one of {})",
+            STRATEGIES.map(|s| s.name()).join(", ")
+        )
+    })?;
+    if n > strategy.max_n() {
+        return Err(format!(
+            "strategy {} supports n <= {}",
+            strategy.name(),
+            strategy.max_n()
+        ));
+    }
+    Ok(strategy.name().to_string())
+}
@@ -0,0 +1,166 @@
+// Layout of a network drawing and its SVG paths for a verification result
+use crate::sorting_network_check_v2::{JobResult, State};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+#[derive(Clone, Debug)]
+pub struct SvgPos {
+    pub n: usize,
+    pub d: usize,
+    pub cmp: Vec<(usize, usize)>,
+    pub width: usize,
+    pub height: usize,
+    pub x_pos: Vec<usize>,
+    // Comparator ranges of each layer, empty for an invalid network
+    pub layers: Vec<Range<usize>>,
+}
+impl SvgPos {
+    pub fn new(n: usize, cmp: &[(usize, usize)]) -> Self {
+        gen_svg_pos(n, cmp)
+    }
+}
+
+pub fn gen_svg_pos(n: usize, cmp: &[(usize, usize)]) -> SvgPos {
+    let x_scale = 35;
+    let x_scale_thin = 11;
+    let y_scale = 20;
+
+    let mut width = x_scale * 2 + x_scale_thin * cmp.len().saturating_sub(1);
+    let height = y_scale * (n + 1);
+    let mut x_pos = (0..cmp.len())
+        .map(|i| i * x_scale_thin + x_scale)
+        .collect::<Vec<_>>();
+    let mut d = 0;
+    if cmp.iter().any(|&(a, b)| a >= n || b >= n || a >= b) {
+        return SvgPos {
+            n,
+            d,
+            cmp: cmp.to_vec(),
+            width,
+            height,
+            x_pos,
+            layers: vec![],
+        };
+    }
+    let mut w = x_scale;
+    let layers = gen_layers(n, cmp);
+    for layer in layers.iter() {
+        d += 1;
+        let mut gfill = Vec::<Vec<bool>>::new();
+        'a: for (i, &(a, b)) in layer.clone().zip(&cmp[layer.clone()]) {
+            for (j, l) in gfill.iter_mut().enumerate() {
+                if l[a..=b].iter().any(|&f| f) {
+                    continue;
+                }
+                x_pos[i] = w + x_scale_thin * j;
+                l[a..=b].fill(true);
+                continue 'a;
+            }
+            x_pos[i] = w + x_scale_thin * gfill.len();
+            gfill.push(vec![false; n]);
+            gfill.last_mut().unwrap()[a..=b].fill(true);
+        }
+        w += gfill.len().saturating_sub(1) * x_scale_thin + x_scale;
+    }
+
+    width = w;
+
+    SvgPos {
+        n,
+        d,
+        cmp: cmp.to_vec(),
+        width,
+        height,
+        x_pos,
+        layers,
+    }
+}
+
+// Layers of a valid network: runs of consecutive comparators on distinct channels, each ending
+// at the first comparator that touches a channel used in it
+pub fn gen_layers(n: usize, cmp: &[(usize, usize)]) -> Vec<Range<usize>> {
+    let mut layers = vec![];
+    let mut used = vec![false; n];
+    let mut start = 0;
+    for (i, &(a, b)) in cmp.iter().enumerate() {
+        if used[a] || used[b] {
+            layers.push(start..i);
+            used.fill(false);
+            start = i;
+        }
+        used[a] = true;
+        used[b] = true;
+    }
+    if start < cmp.len() {
+        layers.push(start..cmp.len());
+    }
+    layers
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SortingNetworkSvg {
+    pub width: usize,
+    pub height: usize,
+    pub path_nodes: String,
+    pub path_cmp_normal: String,
+    pub path_cmp_unused: String,
+    pub path_nodes_unknown: String,
+    pub path_nodes_unsorted: String,
+}
+
+pub fn gen_svg<S: State>(pos: &SvgPos, result: &JobResult<S>) -> SortingNetworkSvg {
+    let x_scale = 35;
+    let y_scale = 20;
+    let r = 3;
+    let r2 = r * 2;
+
+    let mut path_nodes = String::new();
+    let mut path_cmp_normal = String::new();
+    let mut path_cmp_unused = String::new();
+    let mut path_nodes_unknown = String::new();
+    let mut path_nodes_unsorted = String::new();
+
+    for (i, (&(a, b), &x)) in pos.cmp.iter().zip(pos.x_pos.iter()).enumerate() {
+        let y1 = y_scale * (a + 1) + r;
+        let yd = y_scale * (b - a) - r2;
+        let path = format!(
+            "M{x} {y1}a{r} {r} 0 1 1 0-{r2}a{r} {r} 0 1 1 0 {r2}v{yd}a{r} {r} 0 1 1 0 {r2}a{r} {r} 0 1 1 0-{r2}z",
+        );
+        if result.used[i] {
+            path_cmp_normal.push_str(&path);
+        } else {
+            path_cmp_unused.push_str(&path);
+        }
+    }
+    let unsort_x = pos.width - x_scale / 2;
+    for (i, &f) in result.unsorted[..(pos.n.saturating_sub(1))]
+        .iter()
+        .enumerate()
+    {
+        let y = y_scale * (2 * i + 3) / 2 + r;
+        let path = format!("M{unsort_x} {y}a{r} {r} 0 1 1 0-{r2}a{r} {r} 0 1 1 0 {r2}z");
+        if ((f >> 1) >> i) & S::ONE != S::ZERO {
+            path_nodes_unsorted.push_str(&path);
+        } else if result.progress < result.progress_all {
+            path_nodes_unknown.push_str(&path);
+        }
+    }
+
+    for i in 0..pos.n {
+        path_nodes.push_str(&format!(
+            "M0 {y}h{width}",
+            y = y_scale * (i + 1),
+            width = pos.width
+        ));
+    }
+
+    SortingNetworkSvg {
+        width: pos.width,
+        height: pos.height,
+        path_nodes,
+        path_cmp_normal,
+        path_cmp_unused,
+        path_nodes_unknown,
+        path_nodes_unsorted,
+    }
+}
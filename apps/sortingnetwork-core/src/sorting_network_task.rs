@@ -0,0 +1,209 @@
+// The task a frontend sends and the progress it is shown, shared by the Tauri commands and the
+// Web Worker of the browser
+use crate::sorting_network_check_v2::{
+    COUNT_MAX_N, Counterexample, FailureCount, JobResult, State, VerifyOptions,
+};
+use crate::sorting_network_parse::{
+    ParseError, Span, parse_memory_budget, parse_network, parse_preconditions, parse_sorted_blocks,
+    parse_target,
+};
+use crate::sorting_network_strategy::parse_strategy;
+use crate::sorting_network_svg::{SortingNetworkSvg, SvgPos};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TaskParams {
+    pub id: u32,
+    pub net: String,
+    pub blocks: String,
+    pub target: String,
+    pub preconditions: String,
+    pub count: bool,
+    pub memory_budget: String,
+    pub strategy: String,
+    pub timeout: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProgressUpdate {
+    pub n: usize,
+    pub l: usize,
+    pub d: usize,
+    pub max_branches: u64,
+    pub branches: u64,
+    pub used: Vec<bool>,
+    pub unsorted: Vec<Vec<bool>>,
+    pub counterexample: Option<Counterexample>,
+    pub witnesses: Vec<((usize, usize), Counterexample)>,
+    pub wrong: Vec<usize>,
+    pub failure_count: Option<FailureCountUpdate>,
+    pub svg: SortingNetworkSvg,
+    pub time: u64,
+    pub strategy: String,
+    pub log: String,
+}
+impl ProgressUpdate {
+    pub fn new<S: State>(
+        n: usize,
+        l: usize,
+        pos: &SvgPos,
+        progress: JobResult<S>,
+        svg: SortingNetworkSvg,
+    ) -> Self {
+        Self {
+            n,
+            l,
+            d: pos.d,
+            max_branches: progress.progress_all as _,
+            branches: progress.progress as _,
+            unsorted: progress.get_unsorted_matrix(),
+            used: progress.used,
+            counterexample: progress.counterexample,
+            witnesses: progress.witnesses,
+            wrong: progress.wrong,
+            failure_count: progress.failure_count.map(Into::into),
+            svg,
+            time: progress.time,
+            strategy: progress.strategy.to_string(),
+            log: progress.log,
+        }
+    }
+}
+
+// Counts as decimal strings, u128 does not survive the JSON numbers of the frontend
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FailureCountUpdate {
+    pub inputs: String,
+    pub failing: String,
+    pub failing_at: Vec<String>,
+}
+impl From<FailureCount> for FailureCountUpdate {
+    fn from(count: FailureCount) -> Self {
+        Self {
+            inputs: count.inputs.to_string(),
+            failing: count.failing.to_string(),
+            failing_at: count.failing_at.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+// An input the task could not be parsed from, the span locates an error in the network text
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+impl From<ParseError> for InputError {
+    fn from(e: ParseError) -> Self {
+        Self {
+            message: e.to_string(),
+            span: Some(e.span()),
+        }
+    }
+}
+impl From<String> for InputError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            span: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum EmitType {
+    Progress(ProgressUpdate),
+    Log(String),
+    Error(String),
+    InvalidInput(InputError),
+    CancelRecv,
+    Cancelled,
+    // Network of a resumed checkpoint in the procon format
+    Resumed(String),
+    // Stopped by the timeout, with the partial results
+    Timeout(ProgressUpdate),
+    Done,
+}
+
+// Progress of a minimize, verified again in rounds as the unused comparators are removed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MinimizeUpdate {
+    pub round: usize,
+    pub n: usize,
+    pub l: usize,
+    pub removed: usize,
+    pub max_branches: u64,
+    pub branches: u64,
+    pub time: u64,
+    pub log: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum MinimizeEmitType {
+    Progress(MinimizeUpdate),
+    Log(String),
+    Error(String),
+    InvalidInput(InputError),
+    CancelRecv,
+    Cancelled,
+    // Reduced network in the procon format
+    Done(String),
+}
+
+pub type ParsedTask = (usize, usize, Vec<(usize, usize)>, VerifyOptions);
+
+pub fn parse_task(
+    net: &str,
+    blocks: &str,
+    target: &str,
+    preconditions: &str,
+    count: bool,
+    memory_budget: &str,
+    strategy: &str,
+) -> Result<ParsedTask, InputError> {
+    let (n, l, cmp) = parse_network(net)?;
+    let options = VerifyOptions {
+        sorted_blocks: parse_sorted_blocks(n, blocks)?,
+        target: parse_target(n, target)?,
+        preconditions: parse_preconditions(n, preconditions)?,
+        count,
+        memory_budget: parse_memory_budget(memory_budget)?,
+        strategy: parse_strategy(n, strategy)?,
+    };
+    if !options.is_satisfiable(n) {
+        return Err("unsatisfiable preconditions".to_string().into());
+    }
+    if count && n > COUNT_MAX_N {
+        return Err(format!("counting failing inputs needs n <= {COUNT_MAX_N}, n: {n}").into());
+    }
+    Ok((n, l, cmp, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting_network_check_brute::BRUTE_MAX_N;
+    use crate::sorting_network_gen::gen_batcher;
+    use crate::sorting_network_parse::gen_procon;
+
+    // 2^128 inputs overflow the u128 counts
+    #[test]
+    fn count_limit() {
+        for n in [127, 128] {
+            let net = gen_procon(n, &gen_batcher(n));
+            assert!(parse_task(&net, "", "", "", false, "", "").is_ok());
+            let counted = parse_task(&net, "", "", "", true, "", "");
+            assert_eq!(counted.is_ok(), n <= COUNT_MAX_N, "n: {n}");
+        }
+    }
+
+    // The enumeration walks 2^n inputs, so it is limited to BRUTE_MAX_N channels
+    #[test]
+    fn enumerate_limit() {
+        for n in [24, 25] {
+            let net = gen_procon(n, &gen_batcher(n));
+            let task = parse_task(&net, "", "", "", false, "", "enumerate");
+            assert_eq!(task.is_ok(), n <= BRUTE_MAX_N, "n: {n}");
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Exit codes of the command-line verifier: 0 for a sorting network, 1 for No, 2 for an error
+// and 3 for the timeout
+use sortingnetwork_core::sorting_network_gen::gen_batcher;
+use sortingnetwork_core::sorting_network_parse::gen_procon;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], net: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sortingnetwork-verify"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(net.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn exit_codes() {
+    let cmp = gen_batcher(16);
+    let output = run(&[], &gen_procon(16, &cmp));
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Yes"));
+
+    let output = run(&["-"], &gen_procon(16, &cmp[..cmp.len() - 1]));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("No"));
+
+    let output = run(&[], "4 2\n1 x\n2 4");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("line 2, column 3"), "{stderr}");
+    let output = run(&["--strategy", "unknown"], &gen_procon(16, &cmp));
+    assert_eq!(output.status.code(), Some(2));
+
+    // A zero timeout is over before the first job
+    let output = run(&["--timeout", "0"], &gen_procon(16, &cmp));
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    let output = run(&["--timeout", "soon"], &gen_procon(16, &cmp));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+// The options go through the same checks as in the app
+#[test]
+fn options() {
+    let code = |args: &[&str], net: &str| run(args, net).status.code();
+    // Merges two sorted halves, but does not sort
+    let merger = "[(0,2),(1,3),(1,2)]";
+    assert_eq!(code(&[], merger), Some(1));
+    assert_eq!(code(&["--blocks", "1-2 3-4"], merger), Some(0));
+    assert_eq!(code(&["--blocks", "1-5"], merger), Some(2));
+
+    // One bubble pass moves the max to the last position only
+    let bubble = "[(0,1),(1,2),(2,3)]";
+    assert_eq!(code(&["--target", "max"], bubble), Some(0));
+    assert_eq!(code(&["--target", "min"], bubble), Some(1));
+    assert_eq!(code(&["--target", "5"], bubble), Some(2));
+
+    // A fixed 0 at the first input leaves only the last two to sort
+    let pair = "[(1,2)]";
+    assert_eq!(code(&["--preconditions", "1:0"], pair), Some(0));
+    assert_eq!(code(&["--preconditions", "1:0 1:1"], pair), Some(2));
+
+    // Sorting each half leaves 7 of the 16 inputs sorted
+    let output = run(&["--count"], "[(0,1)]\n[(2,3)]");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.ends_with("failing: 9/16\n"), "{stdout}");
+
+    let cmp = gen_batcher(16);
+    let net = gen_procon(16, &cmp);
+    assert_eq!(code(&["--memory-budget", "1K"], &net), Some(2));
+    assert_eq!(code(&["--memory-budget", "lots"], &net), Some(2));
+    assert_eq!(code(&["--strategy", "enumerate"], &net), Some(0));
+    assert_eq!(
+        code(
+            &["--strategy", "enumerate"],
+            &gen_procon(32, &gen_batcher(32))
+        ),
+        Some(2)
+    );
+}
@@ -1,55 +1,40 @@
-use crate::{
-    SortingNetworkVerifyId,
+use crate::{SortingNetworkCheckpoint, SortingNetworkVerifyId};
+use sortingnetwork_core::{
     /*
     sorting_network_check::{
         gen_svg, gen_svg_pos, is_sorting_network_future_v1, parse_network, SortingNetworkSvg, FIB1,
     },
     */
     sorting_network_check_v2::{
-        JobProgress, SortingNetworkSvg, SvgPos, gen_svg, is_sorting_network_future_v2,
-        parse_network, JobResult,
+        self, Checkpoint, JobProgress, JobResultFuture, is_sorting_network_future_v2,
+        load_checkpoint_header, resume_sorting_network_future_v2, estimate_plan, JobResult,
+        State, VerifyOptions, VerifyPlan,
+    },
+    sorting_network_export::{export_network, ExportFormat},
+    sorting_network_hdl::{gen_hdl, HdlOptions},
+    sorting_network_kernel::{gen_kernel, KernelOptions},
+    sorting_network_parse::{gen_procon, parse_memory_budget, parse_network, parse_timeout},
+    sorting_network_svg::{gen_svg, SortingNetworkSvg, SvgPos},
+    sorting_network_task::{
+        parse_task, EmitType, InputError, MinimizeEmitType, MinimizeUpdate, ProgressUpdate,
     },
     threadpool::ThreadPool,
 };
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
+// How long a running task waits for progress before looking for a newer task id
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Serialize, Deserialize)]
 pub struct GreetArgs {
     name: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct TaskParams {
-    id: u32,
-    net: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct ProgressUpdate {
-    n: usize,
-    l: usize,
-    d: usize,
-    max_branches: u64,
-    branches: u64,
-    used: Vec<bool>,
-    unsorted: Vec<Vec<bool>>,
-    svg: SortingNetworkSvg,
-    time: u64,
-    log: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub enum EmitType {
-    Progress(ProgressUpdate),
-    Log(String),
-    Error(String),
-    CancelRecv,
-    Cencelled,
-    Done,
-}
-
 /*
 #[tauri::command]
 pub fn greet(name: String) -> String {
@@ -69,271 +54,548 @@ pub async fn trigger_backend_event(app: AppHandle) {
 }
 */
 
+// The arguments mirror the fields of TaskParams sent by the frontend
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-pub async fn sorting_network_verify(id: u32, net: String, app: AppHandle) -> String {
-    let emit = |x| {
-        app.emit::<(u32, EmitType)>("checkprogress", (id, x))
+pub async fn sorting_network_verify(
+    id: u32,
+    net: String,
+    blocks: String,
+    target: String,
+    preconditions: String,
+    count: bool,
+    memory_budget: String,
+    strategy: String,
+    timeout: String,
+    app: AppHandle,
+) -> String {
+    {
+        app.state::<Mutex<SortingNetworkVerifyId>>()
+            .lock()
             .unwrap()
-    };
-    let get_pool = || app.state::<Arc<ThreadPool>>();
+            .set(id);
+    }
+    let parsed = parse_task(&net, &blocks, &target, &preconditions, count, &memory_budget, &strategy)
+        .and_then(|task| Ok((task, parse_timeout(&timeout)?)));
+    match parsed {
+        // The u64 state covers up to 64 channels, wider networks use u128
+        Ok(((n, l, cmp, options), timeout)) if n <= u64::BITS as usize => {
+            start_network::<u64>(&app, id, n, l, cmp, options, timeout)
+        }
+        Ok(((n, l, cmp, options), timeout)) => {
+            start_network::<u128>(&app, id, n, l, cmp, options, timeout)
+        }
+        Err(e) => {
+            app.emit::<(u32, EmitType)>("checkprogress", (id, EmitType::InvalidInput(e)))
+                .unwrap();
+            "done verify".to_string()
+        }
+    }
+}
+
+fn start_network<S: State>(
+    app: &AppHandle,
+    id: u32,
+    n: usize,
+    l: usize,
+    cmp: Vec<(usize, usize)>,
+    options: VerifyOptions,
+    timeout: Option<Duration>,
+) -> String {
+    let pool = Arc::clone(&app.state::<Arc<ThreadPool>>());
+    let future = is_sorting_network_future_v2::<S>(
+        pool,
+        n,
+        Arc::new(cmp.clone()),
+        Arc::new(options),
+        timeout,
+    );
+    verify_network(app, id, n, l, cmp, future)
+}
+
+// Planned jobs with their estimated states, memory and time, without verifying
+#[tauri::command]
+pub async fn sorting_network_estimate(
+    net: String,
+    blocks: String,
+    target: String,
+    preconditions: String,
+    strategy: String,
+) -> Result<VerifyPlan, InputError> {
+    let (n, _, cmp, options) =
+        parse_task(&net, &blocks, &target, &preconditions, false, "", &strategy)?;
+    Ok(if n <= u64::BITS as usize {
+        estimate_plan::<u64>(n, &cmp, &options)
+    } else {
+        estimate_plan::<u128>(n, &cmp, &options)
+    })
+}
+
+// The network in the format of another tool
+#[tauri::command]
+pub async fn sorting_network_export(net: String, format: ExportFormat) -> Result<String, InputError> {
+    let (n, _, cmp) = parse_network(&net).map_err(InputError::from)?;
+    Ok(export_network(n, &cmp, format))
+}
+
+// A branchless sorting function in C or Rust for the network
+#[tauri::command]
+pub async fn sorting_network_kernel(net: String, options: KernelOptions) -> Result<String, InputError> {
+    let (n, _, cmp) = parse_network(&net).map_err(InputError::from)?;
+    Ok(gen_kernel(n, &cmp, &options)?)
+}
+
+// A Verilog or VHDL sorter module for the network
+#[tauri::command]
+pub async fn sorting_network_hdl(net: String, options: HdlOptions) -> Result<String, InputError> {
+    let (n, _, cmp) = parse_network(&net).map_err(InputError::from)?;
+    Ok(gen_hdl(n, &cmp, &options)?)
+}
+
+// Request a checkpoint of the running verification, written between two of its jobs
+#[tauri::command]
+pub async fn sorting_network_checkpoint(path: String, app: AppHandle) -> String {
+    app.state::<Mutex<SortingNetworkCheckpoint>>()
+        .lock()
+        .unwrap()
+        .request(PathBuf::from(path));
+    "checkpoint requested".to_string()
+}
+
+#[tauri::command]
+pub async fn sorting_network_resume(
+    id: u32,
+    path: String,
+    memory_budget: String,
+    timeout: String,
+    app: AppHandle,
+) -> String {
     {
         app.state::<Mutex<SortingNetworkVerifyId>>()
             .lock()
             .unwrap()
             .set(id);
     }
+    let path = PathBuf::from(path);
+    let parsed = load_checkpoint_header(&path).and_then(|header| {
+        Ok((header, parse_memory_budget(&memory_budget)?, parse_timeout(&timeout)?))
+    });
+    // The states were written with the width chosen from n
+    match parsed {
+        Ok((header, budget, timeout)) if header.bits == u64::BITS => {
+            resume_network::<u64>(&app, id, &path, budget, timeout)
+        }
+        Ok((_, budget, timeout)) => resume_network::<u128>(&app, id, &path, budget, timeout),
+        Err(e) => {
+            app.emit::<(u32, EmitType)>("checkprogress", (id, EmitType::Error(e)))
+                .unwrap();
+            "done verify".to_string()
+        }
+    }
+}
+
+// The memory budget of the resumed run replaces the one of the checkpoint
+fn resume_network<S: State>(
+    app: &AppHandle,
+    id: u32,
+    path: &Path,
+    memory_budget: Option<usize>,
+    timeout: Option<Duration>,
+) -> String {
+    let emit = |x| {
+        app.emit::<(u32, EmitType)>("checkprogress", (id, x))
+            .unwrap()
+    };
+    let mut checkpoint = match Checkpoint::<S>::load(path) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            emit(EmitType::Error(e));
+            return "done verify".to_string();
+        }
+    };
+    checkpoint.header.options.memory_budget = memory_budget;
+    let (n, cmp) = (checkpoint.header.n, checkpoint.header.cmp.clone());
+    emit(EmitType::Resumed(gen_procon(n, &cmp)));
+    let pool = Arc::clone(&app.state::<Arc<ThreadPool>>());
+    let future = resume_sorting_network_future_v2::<S>(pool, checkpoint, timeout);
+    verify_network(app, id, n, cmp.len(), cmp, future)
+}
+
+fn verify_network<S: State>(
+    app: &AppHandle,
+    id: u32,
+    n: usize,
+    l: usize,
+    cmp: Vec<(usize, usize)>,
+    mut future: JobResultFuture<S>,
+) -> String {
+    let emit = |x: EmitType| {
+        // Echo the log on the console, the verifier itself does not print it
+        match &x {
+            EmitType::Progress(ProgressUpdate { log, .. })
+            | EmitType::Timeout(ProgressUpdate { log, .. })
+            | EmitType::Log(log)
+            | EmitType::Error(log) => eprintln!("{log}"),
+            _ => {}
+        }
+        app.emit::<(u32, EmitType)>("checkprogress", (id, x))
+            .unwrap()
+    };
     let get_id = || {
         app.state::<Mutex<SortingNetworkVerifyId>>()
             .lock()
             .unwrap()
             .get()
     };
-    match parse_network(&net) {
-        Ok((n, l, cmp)) => {
-            let pos = SvgPos::new(n, &cmp);
-            let pool = Arc::clone(&get_pool());
-            let mut last_progress = JobResult::new(&cmp);
-            let svg_default = SortingNetworkSvg::default();
-            let mut future = is_sorting_network_future_v2(pool, n, Arc::new(cmp.clone()));
-            loop {
-                if id != get_id() {
-                    future.cancel();
-                    emit(EmitType::CancelRecv);
-                    return "cancelled".to_string();
+    let take_checkpoint = || {
+        app.state::<Mutex<SortingNetworkCheckpoint>>()
+            .lock()
+            .unwrap()
+            .take()
+    };
+    let pos = SvgPos::new(n, &cmp);
+    let mut last_progress = JobResult::<S>::new(n, &cmp);
+    let svg_default = SortingNetworkSvg::default();
+    let timeout_update = |progress: JobResult<S>| {
+        let svg = gen_svg(&pos, &progress);
+        EmitType::Timeout(ProgressUpdate::new(n, l, &pos, progress, svg))
+    };
+    // Drop a request left from an earlier run
+    take_checkpoint();
+    loop {
+        if id != get_id() {
+            future.cancel();
+            emit(EmitType::CancelRecv);
+            return "cancelled".to_string();
+        }
+        if let Some(path) = take_checkpoint() {
+            future.checkpoint(path);
+        }
+        let mut progress_update = match future.recv_progress_timeout(POLL_INTERVAL) {
+            Ok(JobProgress::Progress(progress)) => {
+                last_progress = progress.clone();
+                EmitType::Progress(ProgressUpdate::new(n, l, &pos, progress, svg_default.clone()))
+            }
+            Ok(JobProgress::Log(log)) => {
+                EmitType::Log(log)
+            }
+            Ok(JobProgress::Cancel) => {
+                emit(EmitType::Cancelled);
+                return "cancelled".to_string();
+            }
+            Ok(JobProgress::Done) => {
+                emit(EmitType::Done);
+                return "done verify".to_string();
+            }
+            Ok(JobProgress::Error(e)) => {
+                emit(EmitType::Error(e.clone()));
+                return e;
+            }
+            Ok(JobProgress::Timeout(progress)) => {
+                emit(timeout_update(progress));
+                return "timeout".to_string();
+            }
+            Err(RecvTimeoutError::Timeout) => continue,
+            // The worker reports its panics, so it never ends silently
+            Err(RecvTimeoutError::Disconnected) => {
+                let msg = "error: the verification ended without a result".to_string();
+                emit(EmitType::Error(msg.clone()));
+                return msg;
+            }
+        };
+        // Reduce the frequency of notifications to the frontend
+        loop {
+            match future.try_recv_progress() {
+                Ok(JobProgress::Progress(progress)) => {
+                    let prev_log = match progress_update {
+                        EmitType::Progress(ProgressUpdate { log, .. }) => log + "\n",
+                        EmitType::Log(log) => log + "\n",
+                        _ => String::new(),
+                    };
+                    last_progress = progress.clone();
+                    let mut update =
+                        ProgressUpdate::new(n, l, &pos, progress, svg_default.clone());
+                    update.log = prev_log + &update.log;
+                    progress_update = EmitType::Progress(update);
                 }
-                let mut progress_update = match future.recv_progress() {
-                    Ok(JobProgress::Progress(progress)) => {
-                        last_progress = progress.clone();
-                        EmitType::Progress(ProgressUpdate {
-                            n,
-                            l,
-                            d: pos.d,
-                            max_branches: progress.progress_all as _,
-                            branches: progress.progress as _,
-                            used: progress.used,
-                            unsorted: progress
-                                .unsorted
-                                .iter()
-                                .map(|&x| {
-                                    (0..crate::sorting_network_check_v2::State::BITS)
-                                        .map(|i| (x >> i) & 1 != 0)
-                                        .collect()
-                                })
-                                .collect(),
-                            svg: svg_default.clone(),
-                            time: progress.time,
-                            log: progress.log,
-                        })
-                    }
-                    Ok(JobProgress::Log(log)) => {
-                        EmitType::Log(log)
-                    }
-                    Ok(JobProgress::Cancel) => {
-                        emit(EmitType::Cencelled);
-                        return "cancelled".to_string();
-                    }
-                    Ok(JobProgress::Done) => {
-                        emit(EmitType::Done);
-                        return "done verify".to_string();
-                    }
-                    Err(e) => {
-                        let msg = format!("error: {}", e);
-                        emit(EmitType::Error(msg.clone()));
-                        return msg;
-                    }
-                };
-                // Reduce the frequency of notifications to the frontend
-                loop {
-                    match future.try_recv_progress() {
-                        Ok(JobProgress::Progress(progress)) => {
-                            let prev_log = match progress_update {
-                                EmitType::Progress(ProgressUpdate { log, .. }) => log + "\n",
-                                EmitType::Log(log) => log + "\n",
-                                _ => String::new(),
-                            };
-                            last_progress = progress.clone();
-                            progress_update = EmitType::Progress(ProgressUpdate {
-                                n,
-                                l,
-                                d: pos.d,
-                                max_branches: progress.progress_all as _,
-                                branches: progress.progress as _,
-                                used: progress.used,
-                                unsorted: progress
-                                    .unsorted
-                                    .iter()
-                                    .map(|&x| {
-                                        (0..crate::sorting_network_check_v2::State::BITS)
-                                            .map(|i| (x >> i) & 1 != 0)
-                                            .collect()
-                                    })
-                                    .collect(),
-                                svg: svg_default.clone(),
-                                time: progress.time,
-                                log: prev_log + &progress.log,
-                            });
-                        }
-                        Ok(JobProgress::Log(log)) => {
-                            progress_update = match progress_update {
-                                EmitType::Progress(pu) => {
-                                    EmitType::Progress(ProgressUpdate {
-                                        log: pu.log + "\n" + &log,
-                                        ..pu
-                                    })
-                                }
-                                EmitType::Log(prev_log) => EmitType::Log(prev_log + "\n" + &log),
-                                _ => EmitType::Log(log),
-                            };
-                        }
-                        Ok(JobProgress::Cancel) => {
-                            emit(EmitType::Cencelled);
-                            return "cancelled".to_string();
-                        }
-                        Ok(JobProgress::Done) => {
-                            // Update the SVG
-                            if let EmitType::Progress(pp) = progress_update {
-                                progress_update = EmitType::Progress(ProgressUpdate {
-                                    svg: gen_svg(&pos, &last_progress),
-                                    ..pp
-                                });
-                            };
-                            emit(progress_update);
-                            emit(EmitType::Done);
-                            return "done verify".to_string();
+                Ok(JobProgress::Log(log)) => {
+                    progress_update = match progress_update {
+                        EmitType::Progress(pu) => {
+                            EmitType::Progress(ProgressUpdate {
+                                log: pu.log + "\n" + &log,
+                                ..pu
+                            })
                         }
-                        Err(_) => break,
-                    }
+                        EmitType::Log(prev_log) => EmitType::Log(prev_log + "\n" + &log),
+                        _ => EmitType::Log(log),
+                    };
                 }
-                // Update the SVG
-                if let EmitType::Progress(pp) = progress_update {
-                    progress_update = EmitType::Progress(ProgressUpdate {
-                        svg: gen_svg(&pos, &last_progress),
-                        ..pp
-                    });
-                };
-                if id != get_id() {
-                    future.cancel();
-                    //emit(Err("cancelled".to_string()));
-                    emit(EmitType::CancelRecv);
-                    eprintln!("cancelled");
+                Ok(JobProgress::Cancel) => {
+                    emit(EmitType::Cancelled);
                     return "cancelled".to_string();
                 }
-                emit(progress_update);
-                /*
-                match progress {
-                    Ok(JobProgress::Progress(progress)) => {
-                        let svg = gen_svg(&pos, &progress);
-                        emit(EmitType::Progress(ProgressUpdate {
-                            n,
-                            l,
-                            d: pos.d,
-                            max_branches: progress.progress_all as _,
-                            branches: progress.progress as _,
-                            used: progress.used,
-                            unsorted: progress
-                                .unsorted
-                                .iter()
-                                .map(|&x| {
-                                    (0..crate::sorting_network_check_v2::State::BITS)
-                                        .map(|i| (x >> i) & 1 != 0)
-                                        .collect()
-                                })
-                                .collect(),
-                            svg,
-                            time: progress.time,
-                            log: progress.log,
-                        }));
-                    }
-                    Ok(JobProgress::Log(log)) => {
-                        emit(EmitType::Log(log));
-                    }
-                    Ok(JobProgress::Cancel) => {
-                        emit(EmitType::Cencelled);
-                        return "cancelled".to_string();
-                    }
-                    Ok(JobProgress::Done) => {
-                        emit(EmitType::Done);
-                        return "done verify".to_string();
-                    }
-                    Err(e) => {
-                        let msg = format!("error: {}", e);
-                        emit(EmitType::Error(msg.clone()));
-                        return msg;
-                    }
+                Ok(JobProgress::Done) => {
+                    // Update the SVG
+                    if let EmitType::Progress(pp) = progress_update {
+                        progress_update = EmitType::Progress(ProgressUpdate {
+                            svg: gen_svg(&pos, &last_progress),
+                            ..pp
+                        });
+                    };
+                    emit(progress_update);
+                    emit(EmitType::Done);
+                    return "done verify".to_string();
                 }
-                */
-                if id != get_id() {
-                    future.cancel();
-                    //emit(Err("cancelled".to_string()));
-                    emit(EmitType::CancelRecv);
-                    eprintln!("cancelled");
-                    return "cancelled".to_string();
+                Ok(JobProgress::Error(e)) => {
+                    // Show the partial results before the error
+                    if let EmitType::Progress(pp) = progress_update {
+                        progress_update = EmitType::Progress(ProgressUpdate {
+                            svg: gen_svg(&pos, &last_progress),
+                            ..pp
+                        });
+                    };
+                    emit(progress_update);
+                    emit(EmitType::Error(e.clone()));
+                    return e;
                 }
-            }
-            /*
-            let mut future = is_sorting_network_future_v1(pool, n, Arc::new(cmp.clone()));
-            let fib1_n = FIB1[n];
-            let mut next_progress = 0;
-            let mut prev_used = 0;
-            let mut prev_unsorted = 0;
-            loop {
-                if id != get_id() {
-                    future.cancel();
-                    emit(Err("cancelled".to_string()));
-                    return "cancelled".to_string();
-                }
-                let progress = future.recv_progress();
-                if id != get_id() {
-                    future.cancel();
-                    emit(Err("cancelled".to_string()));
-                    return "cancelled".to_string();
+                Ok(JobProgress::Timeout(progress)) => {
+                    emit(progress_update);
+                    emit(timeout_update(progress));
+                    return "timeout".to_string();
                 }
-                let count_used = progress.used.iter().filter(|&&x| !x).count();
-                let count_unsorted = progress
+                Err(_) => break,
+            }
+        }
+        // Update the SVG
+        if let EmitType::Progress(pp) = progress_update {
+            progress_update = EmitType::Progress(ProgressUpdate {
+                svg: gen_svg(&pos, &last_progress),
+                ..pp
+            });
+        };
+        if id != get_id() {
+            future.cancel();
+            //emit(Err("cancelled".to_string()));
+            emit(EmitType::CancelRecv);
+            eprintln!("cancelled");
+            return "cancelled".to_string();
+        }
+        emit(progress_update);
+        /*
+        match progress {
+            Ok(JobProgress::Progress(progress)) => {
+                let svg = gen_svg(&pos, &progress);
+                emit(EmitType::Progress(ProgressUpdate {
+                    n,
+                    l,
+                    d: pos.d,
+                    max_branches: progress.progress_all as _,
+                    branches: progress.progress as _,
+                    used: progress.used,
+                    unsorted: progress
+                        .unsorted
+                        .iter()
+                        .map(|&x| {
+                            (0..crate::sorting_network_check_v2::State::BITS)
+                                .map(|i| (x >> i) & 1 != 0)
+                                .collect()
+                        })
+                        .collect(),
+                    svg,
+                    time: progress.time,
+                    log: progress.log,
+                }));
+            }
+            Ok(JobProgress::Log(log)) => {
+                emit(EmitType::Log(log));
+            }
+            Ok(JobProgress::Cancel) => {
+                emit(EmitType::Cancelled);
+                return "cancelled".to_string();
+            }
+            Ok(JobProgress::Done) => {
+                emit(EmitType::Done);
+                return "done verify".to_string();
+            }
+            Err(e) => {
+                let msg = format!("error: {}", e);
+                emit(EmitType::Error(msg.clone()));
+                return msg;
+            }
+        }
+        */
+        if id != get_id() {
+            future.cancel();
+            //emit(Err("cancelled".to_string()));
+            emit(EmitType::CancelRecv);
+            eprintln!("cancelled");
+            return "cancelled".to_string();
+        }
+    }
+    /*
+    let mut future = is_sorting_network_future_v1(pool, n, Arc::new(cmp.clone()));
+    let fib1_n = FIB1[n];
+    let mut next_progress = 0;
+    let mut prev_used = 0;
+    let mut prev_unsorted = 0;
+    loop {
+        if id != get_id() {
+            future.cancel();
+            emit(Err("cancelled".to_string()));
+            return "cancelled".to_string();
+        }
+        let progress = future.recv_progress();
+        if id != get_id() {
+            future.cancel();
+            emit(Err("cancelled".to_string()));
+            return "cancelled".to_string();
+        }
+        let count_used = progress.used.iter().filter(|&&x| !x).count();
+        let count_unsorted = progress
+            .unsorted
+            .iter()
+            .map(|&x| x.count_ones() as usize)
+            .sum::<usize>();
+        if progress.branches >= next_progress
+            || count_used != prev_used
+            || count_unsorted != prev_unsorted
+        {
+            let svg = gen_svg(&pos, &progress);
+            emit(Ok(Some(ProgressUpdate {
+                n,
+                l,
+                d: pos.d,
+                max_branches: FIB1[n] as _,
+                branches: progress.branches as _,
+                used: progress.used,
+                unsorted: progress
                     .unsorted
                     .iter()
-                    .map(|&x| x.count_ones() as usize)
-                    .sum::<usize>();
-                if progress.branches >= next_progress
-                    || count_used != prev_used
-                    || count_unsorted != prev_unsorted
-                {
-                    let svg = gen_svg(&pos, &progress);
-                    emit(Ok(Some(ProgressUpdate {
+                    .map(|&x| {
+                        (0..crate::sorting_network_check::State::BITS)
+                            .map(|i| (x >> i) & 1 != 0)
+                            .collect()
+                    })
+                    .collect(),
+                svg,
+                time: begin_time.elapsed().as_millis() as u64,
+            })));
+            let percent = (progress.branches * 100) / fib1_n;
+            next_progress = ((percent + 1) * fib1_n - 1) / 100 + 1;
+            prev_used = count_used;
+            prev_unsorted = count_unsorted;
+        }
+        if progress.branches >= fib1_n {
+            emit(Ok(None));
+            break;
+        }
+    }
+    */
+}
+
+// The arguments mirror the fields of TaskParams sent by the frontend
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn sorting_network_minimize(
+    id: u32,
+    net: String,
+    blocks: String,
+    target: String,
+    preconditions: String,
+    memory_budget: String,
+    strategy: String,
+    app: AppHandle,
+) -> String {
+    {
+        app.state::<Mutex<SortingNetworkVerifyId>>()
+            .lock()
+            .unwrap()
+            .set(id);
+    }
+    // Counting is skipped, every trial network would be counted again
+    match parse_task(&net, &blocks, &target, &preconditions, false, &memory_budget, &strategy) {
+        Ok((n, _, cmp, options)) if n <= u64::BITS as usize => {
+            minimize_network::<u64>(&app, id, n, cmp, options)
+        }
+        Ok((n, _, cmp, options)) => minimize_network::<u128>(&app, id, n, cmp, options),
+        Err(e) => {
+            app.emit::<(u32, MinimizeEmitType)>(
+                "minimizeprogress",
+                (id, MinimizeEmitType::InvalidInput(e)),
+            )
+            .unwrap();
+            "done minimize".to_string()
+        }
+    }
+}
+
+// Emit the rounds of the core minimize, cancelled when a newer task id is set
+fn minimize_network<S: State>(
+    app: &AppHandle,
+    id: u32,
+    n: usize,
+    cmp: Vec<(usize, usize)>,
+    options: VerifyOptions,
+) -> String {
+    let emit = |x: MinimizeEmitType| {
+        // Echo the log on the console, the verifier itself does not print it
+        match &x {
+            MinimizeEmitType::Progress(MinimizeUpdate { log, .. })
+            | MinimizeEmitType::Log(log)
+            | MinimizeEmitType::Error(log) => eprintln!("{log}"),
+            _ => {}
+        }
+        app.emit::<(u32, MinimizeEmitType)>("minimizeprogress", (id, x))
+            .unwrap()
+    };
+    let get_id = || {
+        app.state::<Mutex<SortingNetworkVerifyId>>()
+            .lock()
+            .unwrap()
+            .get()
+    };
+    let pool = Arc::clone(&app.state::<Arc<ThreadPool>>());
+    let l = cmp.len();
+    let minimized = sorting_network_check_v2::minimize_network::<S>(
+        pool,
+        n,
+        cmp,
+        Arc::new(options),
+        POLL_INTERVAL,
+        |round, cmp, progress| {
+            if id != get_id() {
+                emit(MinimizeEmitType::CancelRecv);
+                return false;
+            }
+            match progress {
+                Some(JobProgress::Progress(progress)) => {
+                    emit(MinimizeEmitType::Progress(MinimizeUpdate {
+                        round,
                         n,
-                        l,
-                        d: pos.d,
-                        max_branches: FIB1[n] as _,
-                        branches: progress.branches as _,
-                        used: progress.used,
-                        unsorted: progress
-                            .unsorted
-                            .iter()
-                            .map(|&x| {
-                                (0..crate::sorting_network_check::State::BITS)
-                                    .map(|i| (x >> i) & 1 != 0)
-                                    .collect()
-                            })
-                            .collect(),
-                        svg,
-                        time: begin_time.elapsed().as_millis() as u64,
-                    })));
-                    let percent = (progress.branches * 100) / fib1_n;
-                    next_progress = ((percent + 1) * fib1_n - 1) / 100 + 1;
-                    prev_used = count_used;
-                    prev_unsorted = count_unsorted;
-                }
-                if progress.branches >= fib1_n {
-                    emit(Ok(None));
-                    break;
+                        l: cmp.len(),
+                        removed: l - cmp.len(),
+                        max_branches: progress.progress_all as _,
+                        branches: progress.progress as _,
+                        time: progress.time,
+                        log: progress.log.clone(),
+                    }))
                 }
+                Some(JobProgress::Log(log)) => emit(MinimizeEmitType::Log(log.clone())),
+                Some(JobProgress::Cancel) => emit(MinimizeEmitType::Cancelled),
+                _ => {}
             }
-            */
+            true
+        },
+    );
+    match minimized {
+        Ok(Some(cmp)) => {
+            emit(MinimizeEmitType::Done(gen_procon(n, &cmp)));
+            "done minimize".to_string()
         }
+        Ok(None) => "cancelled".to_string(),
         Err(e) => {
-            emit(EmitType::Error(e));
+            emit(MinimizeEmitType::Error(e.clone()));
+            e
         }
     }
-
-    "done verify".to_string()
 }
@@ -1,7 +1,7 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 mod commands;
-mod sorting_network_check_v2;
-mod threadpool;
+use sortingnetwork_core::threadpool;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::{Listener, Manager};
 
@@ -21,6 +21,19 @@ impl SortingNetworkVerifyId {
     }
 }
 
+// Checkpoint file requested for the running verification
+#[derive(Clone, Default)]
+pub struct SortingNetworkCheckpoint(Option<PathBuf>);
+
+impl SortingNetworkCheckpoint {
+    pub fn request(&mut self, path: PathBuf) {
+        self.0 = Some(path);
+    }
+    pub fn take(&mut self) -> Option<PathBuf> {
+        self.0.take()
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -31,6 +44,7 @@ pub fn run() {
                     .unwrap_or(2),
             )));
             app.manage(Mutex::new(SortingNetworkVerifyId::default()));
+            app.manage(Mutex::new(SortingNetworkCheckpoint::default()));
             app.listen("frontend", move |event| {
                 println!("frontend event: {:?}", event);
             });
@@ -40,6 +54,13 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             //commands::greet,
             commands::sorting_network_verify,
+            commands::sorting_network_minimize,
+            commands::sorting_network_estimate,
+            commands::sorting_network_checkpoint,
+            commands::sorting_network_resume,
+            commands::sorting_network_export,
+            commands::sorting_network_kernel,
+            commands::sorting_network_hdl,
             //commands::trigger_backend_event,
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,4 @@
+// The Web Worker that verifies on the page served without the Tauri backend
+fn main() {
+    src_ui::worker::run_worker()
+}
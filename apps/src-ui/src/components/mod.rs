@@ -1,5 +1,4 @@
 //pub mod event_backend;
 //pub mod event_frontend;
 //pub mod greet;
-pub mod sorting_network_opts;
 pub mod sorting_network_verify;
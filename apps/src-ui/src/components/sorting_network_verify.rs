@@ -1,180 +1,157 @@
 use core::f64;
 
-use crate::components::sorting_network_opts;
+use crate::worker::{VerifyWorker, WorkerRequest, WorkerResponse};
 use futures::stream::StreamExt;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
+use sortingnetwork_core::sorting_network_check_v2::{Counterexample, VerifyPlan};
+use sortingnetwork_core::sorting_network_export::{EXPORT_FORMATS, ExportFormat, export_network};
+use sortingnetwork_core::sorting_network_gen::*;
+use sortingnetwork_core::sorting_network_hdl::{HdlLanguage, HdlOptions, gen_hdl};
+use sortingnetwork_core::sorting_network_kernel::{KernelLanguage, KernelOptions, gen_kernel};
+use sortingnetwork_core::sorting_network_opts;
+use sortingnetwork_core::sorting_network_parse::{Span, gen_procon, parse_network};
+use sortingnetwork_core::sorting_network_strategy::STRATEGIES;
+use sortingnetwork_core::sorting_network_task::{
+    EmitType, FailureCountUpdate, InputError, MinimizeEmitType, TaskParams,
+};
 use thaw::*;
 
 #[derive(Serialize, Deserialize)]
-struct TaskParams {
-    id: u32,
+struct EstimateParams {
     net: String,
+    blocks: String,
+    target: String,
+    preconditions: String,
+    strategy: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-struct ProgressUpdate {
-    n: usize,
-    l: usize,
-    d: usize,
-    max_branches: u64,
-    branches: u64,
-    used: Vec<bool>,
-    unsorted: Vec<Vec<bool>>,
-    svg: SortingNetworkSvg,
-    time: u64,
-    log: String,
+#[derive(Serialize, Deserialize)]
+struct ExportParams {
+    net: String,
+    format: ExportFormat,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-struct SortingNetworkSvg {
-    width: usize,
-    height: usize,
-    path_nodes: String,
-    path_cmp_normal: String,
-    path_cmp_unused: String,
-    path_nodes_unknown: String,
-    path_nodes_unsorted: String,
+#[derive(Serialize, Deserialize)]
+struct KernelParams {
+    net: String,
+    options: KernelOptions,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-enum EmitType {
-    Progress(ProgressUpdate),
-    Log(String),
-    Error(String),
-    CancelRecv,
-    Cencelled,
-    Done,
+#[derive(Serialize, Deserialize)]
+struct HdlParams {
+    net: String,
+    options: HdlOptions,
 }
 
-fn gen_procon(n: usize, cmp: &[(usize, usize)]) -> String {
-    let mut procon = String::new();
-    procon.push_str(&format!("{} {}\n", n, cmp.len()));
-    procon.push_str(
-        &cmp.iter()
-            .map(|&(i, _)| (i + 1).to_string())
-            .collect::<Vec<_>>()
-            .join(" "),
-    );
-    procon.push_str("\n");
-    procon.push_str(
-        &cmp.iter()
-            .map(|&(_, j)| (j + 1).to_string())
-            .collect::<Vec<_>>()
-            .join(" "),
-    );
-    procon.push_str("\n");
-    procon
+#[derive(Serialize, Deserialize)]
+struct CheckpointParams {
+    path: String,
 }
 
-fn gen_bubble_max(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    for p in 0..(2 * n - 3) {
-        for i in ((p & 1)..((p + 1).min(2 * n - p - 2))).step_by(2) {
-            cmp.push((i, i + 1));
-        }
-    }
-    cmp
+#[derive(Serialize, Deserialize)]
+struct ResumeParams {
+    id: u32,
+    path: String,
+    memory_budget: String,
+    timeout: String,
 }
-fn gen_bubble_min(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    for p in 0..(2 * n - 3) {
-        for i in (n.abs_diff(p + 2)..(n - 1)).step_by(2) {
-            cmp.push((i, i + 1));
-        }
-    }
-    cmp
+
+// Checkpoints and minimizing run in the backend only
+const DESKTOP_ONLY: &str = "error: not available in the browser, use the desktop app";
+
+fn join_spaced<T: ToString>(v: &[T]) -> String {
+    v.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
-fn gen_oddeven(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    for p in 0..n {
-        for i in ((p & 1)..(n - 1)).step_by(2) {
-            cmp.push((i, i + 1));
-        }
-    }
-    cmp
+
+fn format_failure_count(c: &FailureCountUpdate) -> String {
+    let ratio =
+        |x: &str| x.parse::<f64>().unwrap_or(0.0) / c.inputs.parse::<f64>().unwrap_or(1.0).max(1.0);
+    format!(
+        "Failing inputs: {failing}/{inputs} ({ratio:.6})\nFailing at: {failing_at}",
+        failing = c.failing,
+        inputs = c.inputs,
+        ratio = ratio(&c.failing),
+        failing_at = c
+            .failing_at
+            .iter()
+            .enumerate()
+            .filter(|&(_, x)| x != "0")
+            .map(|(p, x)| format!("{}:{x} ({:.6})", p + 1, ratio(x)))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
 }
-fn triangular_indices(n: usize) -> (usize, usize) {
-    let r = ((8 * n + 1).isqrt() - 1) / 2;
-    (r, n - r * (r + 1) / 2)
+
+// Estimated counts are prefixed with "~" once they are scaled from a sample
+fn format_plan(plan: &VerifyPlan) -> String {
+    let mib = |bytes: f64| bytes / (1 << 20) as f64;
+    let mut lines = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            format!(
+                "{i}: {job}, len: {approx}{len:.0} ({approx}{generated:.0}), memory: {memory:.1}MiB, time: {time:.1}ms",
+                job = s.job,
+                approx = if s.exact { "" } else { "~" },
+                len = s.len,
+                generated = s.generated,
+                memory = mib(s.memory),
+                time = s.time,
+            )
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "Plan, strategy: {strategy}, jobs: {jobs}, peak memory: {memory:.1}MiB, time: {time:.3}sec",
+        strategy = plan.strategy,
+        jobs = plan.steps.len(),
+        memory = mib(plan.memory),
+        time = plan.time / 1000.0,
+    ));
+    lines.join("\n")
 }
-fn gen_bitonic(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    let r = (n).next_power_of_two().ilog2() as usize;
-    for d in 0..(r * (r + 1) / 2) {
-        let (m, p) = triangular_indices(d);
-        for i in 0..n {
-            let j = if p == 0 {
-                i ^ ((2 << m) - 1)
-            } else {
-                i ^ (1 << (m - p))
-            };
-            if i < j && j < n {
-                cmp.push((i, j));
-            }
+
+// Select the span in the textarea, its selection counts UTF-16 units. A missing line
+// selects the end of the text.
+fn select_span(ta: &web_sys::HtmlTextAreaElement, text: &str, span: Span) {
+    let utf16_len = |s: &str| s.chars().map(char::len_utf16).sum::<usize>();
+    let mut start = 0;
+    let mut lines = text.split('\n');
+    for _ in 1..span.line {
+        match lines.next() {
+            Some(line) => start += utf16_len(line) + 1,
+            None => break,
         }
     }
-    cmp
+    let start = start.min(utf16_len(text));
+    let line = lines.next().unwrap_or("");
+    let column = |c: usize| utf16_len(&line.chars().take(c).collect::<String>());
+    let end = start + column(span.column - 1 + span.len);
+    let start = start + column(span.column - 1);
+    let _ = ta.focus();
+    let _ = ta.set_selection_range(start as u32, end as u32);
 }
-fn gen_batcher(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    let r = (n).next_power_of_two().ilog2() as usize;
-    for d in 0..(r * (r + 1) / 2) {
-        let (m, p) = triangular_indices(d);
-        for i in 0..n {
-            let j = if p == 0 {
-                i ^ (1 << m)
-            } else {
-                let (scale, boxmask) = (m - p, (2usize << p) - 1);
-                let sn = (i >> scale) & boxmask;
-                if sn == 0 || sn == boxmask {
-                    i
-                } else if (sn & 1) == 0 {
-                    i - (1 << scale)
-                } else {
-                    i + (1 << scale)
-                }
-            };
-            if i < j && j < n {
-                cmp.push((i, j));
-            }
+
+fn copy_text(text: String) {
+    let clipboard = window().navigator().clipboard();
+    leptos::task::spawn_local(async move {
+        if let Err(e) = wasm_bindgen_futures::JsFuture::from(clipboard.write_text(&text)).await {
+            log::error!("copy failed: {e:?}");
         }
-    }
-    cmp
+    });
 }
-fn gen_pairwise(n: usize) -> Vec<(usize, usize)> {
-    assert!(2 <= n && n <= 64);
-    let mut cmp = Vec::new();
-    let r = (n).next_power_of_two().ilog2() as usize;
-    for d in 0..(r * (r + 1) / 2) {
-        let (m, p) = if d < r {
-            (0, d)
-        } else {
-            let (tm, tp) = triangular_indices(d - r);
-            (tm + 1, tp)
-        };
-        for i in 0..n {
-            let j = if m == 0 {
-                i ^ (1 << p)
-            } else {
-                let dj = (1 << (r - p - 1)) - (1 << (r - m - 1));
-                if ((i >> (r - m - 1)) & 1) == 0 {
-                    if i >= dj { i - dj } else { i }
-                } else {
-                    i + dj
-                }
-            };
-            if i < j && j < n {
-                cmp.push((i, j));
-            }
-        }
-    }
-    cmp
+
+fn format_counterexample(c: &Counterexample) -> String {
+    format!(
+        "input: {input}\noutput: {output}\npermutation: {permutation}",
+        input = join_spaced(&c.input),
+        output = join_spaced(&c.output),
+        permutation = join_spaced(&c.permutation),
+    )
 }
 
 #[component]
@@ -182,6 +159,14 @@ pub fn SortingNetworkVerify() -> impl IntoView {
     let placeholder = "2 1\n1\n2\n";
     let taskid = RwSignal::new(0u32);
     let net = RwSignal::new(String::new());
+    let blocks = RwSignal::new(String::new());
+    let target = RwSignal::new(String::new());
+    let preconditions = RwSignal::new(String::new());
+    let count = RwSignal::new(false);
+    let checkpoint_path = RwSignal::new(String::new());
+    let memory_budget = RwSignal::new(String::new());
+    let strategy = RwSignal::new(STRATEGIES[0].name().to_string());
+    let timeout = RwSignal::new(String::new());
     let netresult = RwSignal::new(String::new());
     let progress_value = RwSignal::new(0f64);
     let progress_text = RwSignal::new("".to_string());
@@ -195,16 +180,207 @@ pub fn SortingNetworkVerify() -> impl IntoView {
     let svg_path_nodes_unknown = RwSignal::new(String::new());
     let svg_path_nodes_unsorted = RwSignal::new(String::new());
     let select_value = RwSignal::new("Default".to_string());
+    let counterexample = RwSignal::new(String::new());
+    let witnesses = RwSignal::new(Vec::<((usize, usize), Counterexample)>::new());
+    let exported = RwSignal::new(String::new());
+    let kernel_elem = RwSignal::new(String::new());
+    let kernel_self_test = RwSignal::new(false);
+    let hdl_width = RwSignal::new(8usize);
+    let hdl_pipeline = RwSignal::new(false);
+    let hdl_testbench = RwSignal::new(false);
+
+    // Served as a static page, the verification runs in a Web Worker instead of the backend
+    let in_tauri = js_sys::Reflect::has(&window(), &"__TAURI_INTERNALS__".into()).unwrap_or(false);
+    // The running worker, a new task terminates it
+    let worker = StoredValue::new_local(None::<VerifyWorker>);
+    // Progress of the worker, handled as the events of the backend
+    let (worker_tx, worker_rx) = futures::channel::mpsc::unbounded::<(u32, EmitType)>();
+    let worker_tx = StoredValue::new_local(worker_tx);
+
+    let task_params = move || TaskParams {
+        id: taskid.get_untracked(),
+        net: net.get_untracked(),
+        blocks: blocks.get_untracked(),
+        target: target.get_untracked(),
+        preconditions: preconditions.get_untracked(),
+        count: count.get_untracked(),
+        memory_budget: memory_budget.get_untracked(),
+        strategy: strategy.get_untracked(),
+        timeout: timeout.get_untracked(),
+    };
+
+    let net_ref = NodeRef::<leptos::html::Textarea>::new();
+    // Show the message and select the position of a network error
+    let show_input_error = move |e: InputError| {
+        netresult.set(format!("error: {}", e.message));
+        if let (Some(span), Some(ta)) = (e.span, net_ref.get_untracked()) {
+            select_span(&ta, &net.get_untracked(), span);
+        }
+    };
+    let show_plan = move |plan: Result<VerifyPlan, InputError>| match plan {
+        Ok(plan) => netresult.set(format_plan(&plan)),
+        Err(e) => show_input_error(e),
+    };
+
+    let run_in_worker = move |request: WorkerRequest| {
+        let on_response = move |json: String| match serde_json::from_str::<WorkerResponse>(&json) {
+            Ok(WorkerResponse::Progress(id, x)) => {
+                worker_tx.with_value(|tx| tx.unbounded_send((id, *x)).ok());
+            }
+            Ok(WorkerResponse::Plan(plan)) => show_plan(plan),
+            Err(e) => log::error!("worker: {e}"),
+        };
+        // Drop the previous worker first, its late messages would mix with the new ones
+        worker.set_value(None);
+        match VerifyWorker::start(&request, on_response) {
+            Ok(w) => worker.set_value(Some(w)),
+            Err(e) => netresult.set(format!("error: {e:?}")),
+        }
+    };
 
     let on_click = move |_: leptos::ev::MouseEvent| {
         netresult.set("*in progress*".to_string());
+        counterexample.set(String::new());
+        witnesses.set(vec![]);
         taskid.set(taskid.get_untracked().wrapping_add(1));
+        if !in_tauri {
+            run_in_worker(WorkerRequest::Verify(task_params()));
+            return;
+        }
+        leptos::task::spawn_local(async move {
+            let result: String =
+                tauri_sys::core::invoke("sorting_network_verify", task_params()).await;
+            log::info!("result: {:?}", result);
+        });
+    };
+
+    let on_estimate = move |_: leptos::ev::MouseEvent| {
+        netresult.set("*estimating*".to_string());
+        if !in_tauri {
+            run_in_worker(WorkerRequest::Estimate(task_params()));
+            return;
+        }
+        leptos::task::spawn_local(async move {
+            let result = tauri_sys::core::invoke_result::<VerifyPlan, InputError>(
+                "sorting_network_estimate",
+                EstimateParams {
+                    net: net.get_untracked(),
+                    blocks: blocks.get_untracked(),
+                    target: target.get_untracked(),
+                    preconditions: preconditions.get_untracked(),
+                    strategy: strategy.get_untracked(),
+                },
+            )
+            .await;
+            show_plan(result);
+        });
+    };
+
+    let show_exported = move |result: Result<String, InputError>| match result {
+        Ok(text) => exported.set(text),
+        Err(e) => {
+            exported.set(String::new());
+            show_input_error(e);
+        }
+    };
+    // Without the backend the exports run in place, they are cheap
+    let on_export = move |format: ExportFormat| {
+        let text = net.get_untracked();
+        leptos::task::spawn_local(async move {
+            let result = if in_tauri {
+                tauri_sys::core::invoke_result::<String, InputError>(
+                    "sorting_network_export",
+                    ExportParams { net: text, format },
+                )
+                .await
+            } else {
+                parse_network(&text)
+                    .map(|(n, _, cmp)| export_network(n, &cmp, format))
+                    .map_err(InputError::from)
+            };
+            show_exported(result);
+        });
+    };
+    let on_kernel = move |language: KernelLanguage| {
+        let text = net.get_untracked();
+        let options = KernelOptions {
+            language,
+            elem: kernel_elem.get_untracked(),
+            self_test: kernel_self_test.get_untracked(),
+        };
+        leptos::task::spawn_local(async move {
+            let result = if in_tauri {
+                tauri_sys::core::invoke_result::<String, InputError>(
+                    "sorting_network_kernel",
+                    KernelParams { net: text, options },
+                )
+                .await
+            } else {
+                parse_network(&text)
+                    .map_err(InputError::from)
+                    .and_then(|(n, _, cmp)| Ok(gen_kernel(n, &cmp, &options)?))
+            };
+            show_exported(result);
+        });
+    };
+    let on_hdl = move |language: HdlLanguage| {
+        let text = net.get_untracked();
+        let options = HdlOptions {
+            language,
+            width: hdl_width.get_untracked(),
+            pipeline: hdl_pipeline.get_untracked(),
+            testbench: hdl_testbench.get_untracked(),
+        };
+        leptos::task::spawn_local(async move {
+            let result = if in_tauri {
+                tauri_sys::core::invoke_result::<String, InputError>(
+                    "sorting_network_hdl",
+                    HdlParams { net: text, options },
+                )
+                .await
+            } else {
+                parse_network(&text)
+                    .map_err(InputError::from)
+                    .and_then(|(n, _, cmp)| Ok(gen_hdl(n, &cmp, &options)?))
+            };
+            show_exported(result);
+        });
+    };
+
+    let on_checkpoint = move |_: leptos::ev::MouseEvent| {
+        if !in_tauri {
+            netresult.set(DESKTOP_ONLY.to_string());
+            return;
+        }
         leptos::task::spawn_local(async move {
             let result: String = tauri_sys::core::invoke(
-                "sorting_network_verify",
-                TaskParams {
+                "sorting_network_checkpoint",
+                CheckpointParams {
+                    path: checkpoint_path.get_untracked(),
+                },
+            )
+            .await;
+            log::info!("result: {:?}", result);
+        });
+    };
+
+    let on_resume = move |_: leptos::ev::MouseEvent| {
+        if !in_tauri {
+            netresult.set(DESKTOP_ONLY.to_string());
+            return;
+        }
+        netresult.set("*resuming*".to_string());
+        counterexample.set(String::new());
+        witnesses.set(vec![]);
+        taskid.set(taskid.get_untracked().wrapping_add(1));
+        leptos::task::spawn_local(async move {
+            let result: String = tauri_sys::core::invoke(
+                "sorting_network_resume",
+                ResumeParams {
                     id: taskid.get_untracked(),
-                    net: net.get_untracked(),
+                    path: checkpoint_path.get_untracked(),
+                    memory_budget: memory_budget.get_untracked(),
+                    timeout: timeout.get_untracked(),
                 },
             )
             .await;
@@ -212,6 +388,22 @@ pub fn SortingNetworkVerify() -> impl IntoView {
         });
     };
 
+    let on_minimize = move |_: leptos::ev::MouseEvent| {
+        if !in_tauri {
+            netresult.set(DESKTOP_ONLY.to_string());
+            return;
+        }
+        netresult.set("*minimizing*".to_string());
+        counterexample.set(String::new());
+        witnesses.set(vec![]);
+        taskid.set(taskid.get_untracked().wrapping_add(1));
+        leptos::task::spawn_local(async move {
+            let result: String =
+                tauri_sys::core::invoke("sorting_network_minimize", task_params()).await;
+            log::info!("result: {:?}", result);
+        });
+    };
+
     let ev_bubble_max = move |_| {
         net.set(gen_procon(
             n_value.get_untracked(),
@@ -275,21 +467,30 @@ pub fn SortingNetworkVerify() -> impl IntoView {
             }
         }
     };
+    let ev_copy_counterexample = move |_| copy_text(counterexample.get_untracked());
+    let ev_copy_exported = move |_| copy_text(exported.get_untracked());
     let ta_ref = NodeRef::<leptos::html::Textarea>::new();
 
     leptos::task::spawn_local(async move {
-        let mut listener = tauri_sys::event::listen::<(u32, EmitType)>("checkprogress")
-            .await
-            .unwrap();
+        let mut events = if in_tauri {
+            tauri_sys::event::listen::<(u32, EmitType)>("checkprogress")
+                .await
+                .unwrap()
+                .map(|event| event.payload)
+                .boxed_local()
+        } else {
+            worker_rx.boxed_local()
+        };
 
-        while let Some(event) = listener.next().await {
-            match event.payload {
+        while let Some(payload) = events.next().await {
+            match payload {
                 (_id, EmitType::Progress(x)) => {
                     log::info!("progress: {:?}", x);
                     progress_value.set((x.branches as f64) / (x.max_branches.max(1) as f64));
                     progress_text.set(format!(
-                        "n: {n}, l: {l}, d: {d}, progress: {percent}%, elapsed: {elapsed:.3}sec, unused_cmp {unused}/{unused_all}, unsorted {unsorted}/{unsorted_all} ({unsorted_d}/{unsorted_d_all})",
+                        "n: {n}, l: {l}, d: {d}, strategy: {strategy}, progress: {percent}%, elapsed: {elapsed:.3}sec, unused_cmp {unused}/{unused_all}, unsorted {unsorted}/{unsorted_all} ({unsorted_d}/{unsorted_d_all})",
                         n = x.n,
+                        strategy = x.strategy,
                         l = x.l,
                         d = x.d,
                         percent = (x.branches * 100) / x.max_branches.max(1),
@@ -315,6 +516,13 @@ pub fn SortingNetworkVerify() -> impl IntoView {
                     svg_path_nodes_unknown.set(x.svg.path_nodes_unknown);
                     svg_path_nodes_unsorted.set(x.svg.path_nodes_unsorted);
                     if x.branches == x.max_branches {
+                        counterexample.set(
+                            x.counterexample
+                                .as_ref()
+                                .map(format_counterexample)
+                                .unwrap_or_default(),
+                        );
+                        witnesses.set(x.witnesses.clone());
                         let yes_no = x.unsorted.iter().flatten().all(|&x| !x);
                         let unused_indexes = x
                             .used
@@ -322,9 +530,14 @@ pub fn SortingNetworkVerify() -> impl IntoView {
                             .enumerate()
                             .filter_map(|(i, &x)| if !x { Some(i + 1) } else { None })
                             .collect::<Vec<_>>();
-                        let unsorted_indexes = (1..64)
-                            .filter_map(|i| if x.unsorted[i - 1][i] { Some(i) } else { None })
-                            .collect::<Vec<_>>();
+                        // Wrong output positions for a selection target, unsorted adjacent pairs otherwise
+                        let unsorted_indexes = if target.get_untracked().trim().is_empty() {
+                            (1..x.n)
+                                .filter_map(|i| if x.unsorted[i - 1][i] { Some(i) } else { None })
+                                .collect::<Vec<_>>()
+                        } else {
+                            x.wrong.iter().map(|&p| p + 1).collect::<Vec<_>>()
+                        };
                         netresult.set(format!(
                             "{log}\n---\n{yes_no}\n{len}\n{indexes}\n",
                             log = netresult.get_untracked(),
@@ -344,6 +557,13 @@ pub fn SortingNetworkVerify() -> impl IntoView {
                             .collect::<Vec<_>>()
                             .join(" "),
                         ));
+                        if let Some(c) = &x.failure_count {
+                            netresult.set(format!(
+                                "{log}{count}\n",
+                                log = netresult.get_untracked(),
+                                count = format_failure_count(c),
+                            ));
+                        }
                     }
                     leptos::task::spawn_local(async move {
                         if let Some(ta) = ta_ref.get() {
@@ -360,9 +580,17 @@ pub fn SortingNetworkVerify() -> impl IntoView {
                     });
                 }
                 (id, EmitType::Error(e)) => {
-                    netresult.set(format!("error: {e:?}"));
+                    // Keep the log of a verification stopped halfway
+                    netresult.set(format!(
+                        "{prev}\nerror: {e:?}",
+                        prev = netresult.get_untracked()
+                    ));
                     log::error!("{id}: error: {e:?}");
                 }
+                (id, EmitType::InvalidInput(e)) => {
+                    log::error!("{id}: invalid input: {e:?}");
+                    show_input_error(e);
+                }
                 (_id, EmitType::Log(e)) => {
                     netresult.set(format!("{prev}\n{e}", prev = netresult.get_untracked()));
                     leptos::task::spawn_local(async move {
@@ -371,7 +599,29 @@ pub fn SortingNetworkVerify() -> impl IntoView {
                         }
                     });
                 }
-                (id, EmitType::Cencelled) => {
+                (id, EmitType::Timeout(x)) => {
+                    // Keep the partial results of the completed jobs
+                    log::info!("{id}: timeout");
+                    progress_value.set((x.branches as f64) / (x.max_branches.max(1) as f64));
+                    netresult.set(format!(
+                        "{prev}\n{log}\n---\nTimeout\n",
+                        prev = netresult.get_untracked(),
+                        log = x.log,
+                    ));
+                    svg_width.set(x.svg.width);
+                    svg_height.set(x.svg.height);
+                    svg_view_box.set(format!("0 0 {} {}", x.svg.width, x.svg.height));
+                    svg_path_nodes.set(x.svg.path_nodes);
+                    svg_path_cmp_normal.set(x.svg.path_cmp_normal);
+                    svg_path_cmp_unused.set(x.svg.path_cmp_unused);
+                    svg_path_nodes_unknown.set(x.svg.path_nodes_unknown);
+                    svg_path_nodes_unsorted.set(x.svg.path_nodes_unsorted);
+                }
+                (id, EmitType::Resumed(procon)) => {
+                    log::info!("{id}: resumed");
+                    net.set(procon);
+                }
+                (id, EmitType::Cancelled) => {
                     netresult.set("cancelled".to_string());
                     log::info!("{id}: cancelled");
                 }
@@ -382,13 +632,79 @@ pub fn SortingNetworkVerify() -> impl IntoView {
         }
     });
 
+    // Without the backend there is nothing to listen to
+    leptos::task::spawn_local(async move {
+        if !in_tauri {
+            return;
+        }
+        let mut listener = tauri_sys::event::listen::<(u32, MinimizeEmitType)>("minimizeprogress")
+            .await
+            .unwrap();
+
+        while let Some(event) = listener.next().await {
+            match event.payload {
+                (_id, MinimizeEmitType::Progress(x)) => {
+                    progress_value.set((x.branches as f64) / (x.max_branches.max(1) as f64));
+                    progress_text.set(format!(
+                        "minimize round: {round}, n: {n}, l: {l}, removed: {removed}, progress: {percent}%, elapsed: {elapsed:.3}sec",
+                        round = x.round,
+                        n = x.n,
+                        l = x.l,
+                        removed = x.removed,
+                        percent = (x.branches * 100) / x.max_branches.max(1),
+                        elapsed = x.time as f64 / 1000.0,
+                    ));
+                    netresult.set(format!(
+                        "{prev}\n{e}",
+                        prev = netresult.get_untracked(),
+                        e = x.log
+                    ));
+                    leptos::task::spawn_local(async move {
+                        if let Some(ta) = ta_ref.get() {
+                            ta.set_scroll_top(ta.scroll_height());
+                        }
+                    });
+                }
+                (_id, MinimizeEmitType::Log(e)) => {
+                    netresult.set(format!("{prev}\n{e}", prev = netresult.get_untracked()));
+                    leptos::task::spawn_local(async move {
+                        if let Some(ta) = ta_ref.get() {
+                            ta.set_scroll_top(ta.scroll_height());
+                        }
+                    });
+                }
+                (id, MinimizeEmitType::Error(e)) => {
+                    netresult.set(format!("error: {e:?}"));
+                    log::error!("{id}: error: {e:?}");
+                }
+                (id, MinimizeEmitType::InvalidInput(e)) => {
+                    log::error!("{id}: invalid input: {e:?}");
+                    show_input_error(e);
+                }
+                (id, MinimizeEmitType::Cancelled) => {
+                    netresult.set("cancelled".to_string());
+                    log::info!("{id}: cancelled");
+                }
+                (id, MinimizeEmitType::CancelRecv) => {
+                    log::info!("{id}: cancel recv");
+                }
+                (id, MinimizeEmitType::Done(procon)) => {
+                    log::info!("{id}: minimized");
+                    // Show the reduced network and verify it
+                    net.set(procon);
+                    on_click(leptos::ev::MouseEvent::new("click").unwrap());
+                }
+            }
+        }
+    });
+
     //             N:<input type="number" min=2 max=64 placeholder="N" prop:value=move || n_value.get() on:input:target=move |ev| n_value.set(ev.target().value().parse().unwrap_or(2)) />
     //             N:<SpinButton<usize> min=2 max=64 step_page=1 value=n_value />
 
     view! {
         <ConfigProvider>
             <p>
-            N:<input type="number" min=2 max=64 placeholder="N" prop:value=move || n_value.get() on:input:target=move |ev| n_value.set(ev.target().value().parse().unwrap_or(2)) />
+            N:<input type="number" min=2 max=128 placeholder="N" prop:value=move || n_value.get() on:input:target=move |ev| n_value.set(ev.target().value().parse().unwrap_or(2)) />
             <Button appearance=ButtonAppearance::Secondary on_click=ev_bubble_max>"BubbleMax"</Button>
             <Button appearance=ButtonAppearance::Secondary on_click=ev_bubble_min>"BubbleMin"</Button>
             <Button appearance=ButtonAppearance::Secondary on_click=ev_oddeven>"OddEven"</Button>
@@ -409,15 +725,78 @@ pub fn SortingNetworkVerify() -> impl IntoView {
             </Select>
             </div>
             <div>
+            "Sorted blocks:"<input type="text" placeholder="e.g. 1-8 9-16" prop:value=move || blocks.get() on:input:target=move |ev| blocks.set(ev.target().value()) />
+            "Target:"<input type="text" placeholder="e.g. min max, median, top4" prop:value=move || target.get() on:input:target=move |ev| target.set(ev.target().value()) />
+            "Preconditions:"<input type="text" placeholder="e.g. 3:0 2<=5 ksorted:4" prop:value=move || preconditions.get() on:input:target=move |ev| preconditions.set(ev.target().value()) />
+            "Memory budget:"<input type="text" placeholder="e.g. 8G, MiB if no unit" prop:value=move || memory_budget.get() on:input:target=move |ev| memory_budget.set(ev.target().value()) />
+            "Timeout:"<input type="text" placeholder="e.g. 90, 30m, 2h" prop:value=move || timeout.get() on:input:target=move |ev| timeout.set(ev.target().value()) />
+            "Strategy:"<select prop:value=move || strategy.get() on:change:target=move |ev| strategy.set(ev.target().value())>
+                {STRATEGIES.map(|s| view! { <option value=s.name()>{s.name()}</option> }).collect_view()}
+            </select>
+            <label><input type="checkbox" prop:checked=move || count.get() on:change:target=move |ev| count.set(ev.target().checked()) />"Count failing inputs"</label>
+            </div>
+            <div>
             <ProgressBar value=progress_value />
             </div>
             <div class="row">
                 <p>{progress_text}</p>
                 //<p><progress max=100 value=progress_value></progress></p>
                 <p><Button appearance=ButtonAppearance::Secondary on_click>"Verify"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=on_minimize>"Minimize"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=on_estimate>"Estimate"</Button></p>
+            </div>
+            <div class="row">
+                <p>"Checkpoint file:"<input type="text" placeholder="e.g. verify.ckpt" prop:value=move || checkpoint_path.get() on:input:target=move |ev| checkpoint_path.set(ev.target().value()) /></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=on_checkpoint>"Checkpoint"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=on_resume>"Resume"</Button></p>
+            </div>
+            <div class="row">
+                <p>"Export:"</p>
+                {EXPORT_FORMATS.map(|format| view! {
+                    <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_export(format)>{format.name()}</Button></p>
+                }).collect_view()}
             </div>
-            <textarea rows=4 placeholder=placeholder prop:value=move || net.get() on:input:target=move |ev| net.set(ev.target().value()) class="network"></textarea>
+            <div class="row">
+                <p>"Kernel:"<input type="text" placeholder="element type, e.g. int32_t, u32" prop:value=move || kernel_elem.get() on:input:target=move |ev| kernel_elem.set(ev.target().value()) /></p>
+                <p><label><input type="checkbox" prop:checked=move || kernel_self_test.get() on:change:target=move |ev| kernel_self_test.set(ev.target().checked()) />"Self-test"</label></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_kernel(KernelLanguage::C)>"C"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_kernel(KernelLanguage::Rust)>"Rust"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_kernel(KernelLanguage::RustSimd)>"Rust SIMD"</Button></p>
+            </div>
+            <div class="row">
+                <p>"HDL width:"<input type="number" min=1 placeholder="bits" prop:value=move || hdl_width.get() on:input:target=move |ev| hdl_width.set(ev.target().value().parse().unwrap_or(8)) /></p>
+                <p><label><input type="checkbox" prop:checked=move || hdl_pipeline.get() on:change:target=move |ev| hdl_pipeline.set(ev.target().checked()) />"Pipeline"</label></p>
+                <p><label><input type="checkbox" prop:checked=move || hdl_testbench.get() on:change:target=move |ev| hdl_testbench.set(ev.target().checked()) />"Testbench"</label></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_hdl(HdlLanguage::Verilog)>"Verilog"</Button></p>
+                <p><Button appearance=ButtonAppearance::Secondary on_click=move |_| on_hdl(HdlLanguage::Vhdl)>"VHDL"</Button></p>
+            </div>
+            <textarea rows=4 placeholder=placeholder prop:value=move || net.get() on:input:target=move |ev| net.set(ev.target().value()) class="network" node_ref=net_ref></textarea>
             <textarea rows=4 prop:value=move || netresult.get() class="network" readonly node_ref=ta_ref></textarea>
+            <Show when=move || !exported.get().is_empty()>
+                <div class="row">
+                    <textarea rows=3 prop:value=move || exported.get() class="network" readonly></textarea>
+                    <p><Button appearance=ButtonAppearance::Secondary on_click=ev_copy_exported>"Copy"</Button></p>
+                </div>
+            </Show>
+            <Show when=move || !counterexample.get().is_empty()>
+                <div class="row">
+                    <textarea rows=3 prop:value=move || counterexample.get() class="network" readonly></textarea>
+                    <p><Button appearance=ButtonAppearance::Secondary on_click=ev_copy_counterexample>"Copy"</Button></p>
+                </div>
+            </Show>
+            <Show when=move || !witnesses.get().is_empty()>
+                <div class="witnesses">
+                    {move || witnesses.get().into_iter().map(|((i, j), c)| {
+                        let ones = c.input.iter().filter(|&&x| x != 0).count();
+                        let text = format!("pair: {} {}\n{}", i + 1, j + 1, format_counterexample(&c));
+                        view! {
+                            <Button appearance=ButtonAppearance::Subtle on_click=move |_| counterexample.set(text.clone())>
+                                {format!("({}, {}) ones: {}", i + 1, j + 1, ones)}
+                            </Button>
+                        }
+                    }).collect_view()}
+                </div>
+            </Show>
             <div class="network">
                 <svg viewBox=svg_view_box class="network">
                     <rect x=0 y=0 width=svg_width height=svg_height fill="white" />
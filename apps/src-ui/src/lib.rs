@@ -3,6 +3,7 @@ use leptos::prelude::*;
 
 mod app;
 pub mod components;
+pub mod worker;
 
 pub fn run() {
     console_log::init().expect("error initializing logger");
@@ -0,0 +1,171 @@
+// Verification in a Web Worker, for the page served without the Tauri backend. The worker runs
+// the core crate on its own thread and posts the same progress as the backend emits.
+use serde::{Deserialize, Serialize};
+use sortingnetwork_core::sorting_network_check_v2::{
+    Checkpoint, JobProgress, State, VerifyOptions, VerifyPlan, estimate_plan,
+    verify_on_current_thread,
+};
+use sortingnetwork_core::sorting_network_parse::parse_timeout;
+use sortingnetwork_core::sorting_network_svg::{SvgPos, gen_svg};
+use sortingnetwork_core::sorting_network_task::{
+    EmitType, InputError, ProgressUpdate, TaskParams, parse_task,
+};
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent, Worker};
+
+// Posted by the worker once its module is loaded and it listens for the request
+const READY: &str = "ready";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum WorkerRequest {
+    Verify(TaskParams),
+    Estimate(TaskParams),
+}
+
+// Read by the page as well, the progress in the same types as the backend events
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum WorkerResponse {
+    Progress(u32, Box<EmitType>),
+    Plan(Result<VerifyPlan, InputError>),
+}
+
+// The page side of a worker running one request; dropping it stops the verification
+pub struct VerifyWorker {
+    worker: Worker,
+    _on_message: Closure<dyn Fn(MessageEvent)>,
+}
+
+impl VerifyWorker {
+    // `on_response` gets each WorkerResponse as JSON
+    pub fn start(
+        request: &WorkerRequest,
+        on_response: impl Fn(String) + 'static,
+    ) -> Result<Self, JsValue> {
+        // The loader of the verify_worker binary, generated by Trunk
+        let worker = Worker::new("./verify_worker_loader.js")?;
+        let request = serde_json::to_string(request).unwrap();
+        let ready_worker = worker.clone();
+        let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |e: MessageEvent| {
+            let Some(json) = e.data().as_string() else {
+                return;
+            };
+            // A request posted before the module is loaded would be lost
+            if json == READY {
+                ready_worker.post_message(&JsValue::from_str(&request)).ok();
+            } else {
+                on_response(json);
+            }
+        });
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        Ok(Self {
+            worker,
+            _on_message: on_message,
+        })
+    }
+}
+
+impl Drop for VerifyWorker {
+    fn drop(&mut self) {
+        self.worker.set_onmessage(None);
+        self.worker.terminate();
+    }
+}
+
+// Entry of the verify_worker binary
+pub fn run_worker() {
+    console_error_panic_hook::set_once();
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    let post_scope = scope.clone();
+    let post = move |response: WorkerResponse| {
+        let json = serde_json::to_string(&response).unwrap();
+        post_scope.post_message(&JsValue::from_str(&json)).ok();
+    };
+    let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |e: MessageEvent| {
+        let request = e
+            .data()
+            .as_string()
+            .and_then(|json| serde_json::from_str::<WorkerRequest>(&json).ok());
+        match request {
+            Some(request) => handle_request(request, &post),
+            None => web_sys::console::error_1(&"verify worker: invalid request".into()),
+        }
+    });
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+    scope.post_message(&JsValue::from_str(READY)).ok();
+}
+
+fn handle_request(request: WorkerRequest, post: &impl Fn(WorkerResponse)) {
+    match request {
+        WorkerRequest::Verify(p) => {
+            let emit = |x| post(WorkerResponse::Progress(p.id, Box::new(x)));
+            let parsed = parse_task(
+                &p.net,
+                &p.blocks,
+                &p.target,
+                &p.preconditions,
+                p.count,
+                &p.memory_budget,
+                &p.strategy,
+            )
+            .and_then(|task| Ok((task, parse_timeout(&p.timeout)?)));
+            match parsed {
+                // The u64 state covers up to 64 channels, wider networks use u128
+                Ok(((n, l, cmp, options), timeout)) if n <= u64::BITS as usize => {
+                    verify::<u64>(n, l, cmp, options, timeout, &emit)
+                }
+                Ok(((n, l, cmp, options), timeout)) => {
+                    verify::<u128>(n, l, cmp, options, timeout, &emit)
+                }
+                Err(e) => emit(EmitType::InvalidInput(e)),
+            }
+        }
+        WorkerRequest::Estimate(p) => {
+            let plan = parse_task(
+                &p.net,
+                &p.blocks,
+                &p.target,
+                &p.preconditions,
+                false,
+                "",
+                &p.strategy,
+            )
+            .map(|(n, _, cmp, options)| {
+                if n <= u64::BITS as usize {
+                    estimate_plan::<u64>(n, &cmp, &options)
+                } else {
+                    estimate_plan::<u128>(n, &cmp, &options)
+                }
+            });
+            post(WorkerResponse::Plan(plan));
+        }
+    }
+}
+
+// Each progress is posted with its SVG as it comes, the page keeps up with the worker
+fn verify<S: State>(
+    n: usize,
+    l: usize,
+    cmp: Vec<(usize, usize)>,
+    options: VerifyOptions,
+    timeout: Option<Duration>,
+    emit: &impl Fn(EmitType),
+) {
+    let pos = SvgPos::new(n, &cmp);
+    let update = |progress| {
+        let svg = gen_svg(&pos, &progress);
+        ProgressUpdate::new(n, l, &pos, progress, svg)
+    };
+    let checkpoint = Checkpoint::<S>::new(n, &cmp, &options);
+    verify_on_current_thread(checkpoint, timeout, |progress| {
+        emit(match progress {
+            JobProgress::Progress(progress) => EmitType::Progress(update(progress)),
+            JobProgress::Log(log) => EmitType::Log(log),
+            JobProgress::Cancel => EmitType::Cancelled,
+            JobProgress::Done => EmitType::Done,
+            JobProgress::Error(e) => EmitType::Error(e),
+            JobProgress::Timeout(progress) => EmitType::Timeout(update(progress)),
+        })
+    });
+}